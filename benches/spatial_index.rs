@@ -0,0 +1,122 @@
+//! Compares the per-tick spatial grid against rebuilding a kd-tree of the same enemies.
+//!
+//! Run with `cargo bench --bench spatial_index`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use kd_tree::{KdPoint, KdTree};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use shooter2d::{spatial::SpatialGrid, SPATIAL_GRID_CELL_SIZE, WORLD_H, WORLD_W};
+
+const ENEMY_COUNTS: [usize; 3] = [500, 5_000, 20_000];
+const NUM_QUERIES: usize = 500;
+const QUERY_RADIUS: f32 = 40.0;
+const ITERATIONS: u32 = 100;
+
+struct Collidable {
+    position: Vec2,
+    entity: Entity,
+}
+
+impl KdPoint for Collidable {
+    type Scalar = f32;
+    type Dim = typenum::U2;
+
+    fn at(&self, i: usize) -> Self::Scalar {
+        if i == 0 {
+            return self.position.x;
+        }
+
+        self.position.y
+    }
+}
+
+fn random_positions(rng: &mut StdRng, count: usize) -> Vec<Vec2> {
+    (0..count)
+        .map(|_| {
+            Vec2::new(
+                rng.gen_range(-WORLD_W..WORLD_W),
+                rng.gen_range(-WORLD_H..WORLD_H),
+            )
+        })
+        .collect()
+}
+
+fn bench_kd_tree(enemies: &[Vec2], queries: &[Vec2]) -> (Duration, Duration) {
+    let mut build = Duration::ZERO;
+    let mut query = Duration::ZERO;
+
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        let items = enemies
+            .iter()
+            .enumerate()
+            .map(|(i, position)| Collidable {
+                position: *position,
+                entity: Entity::from_raw(i as u32),
+            })
+            .collect();
+        let tree = KdTree::build_by_ordered_float(items);
+        build += start.elapsed();
+
+        let start = Instant::now();
+        for position in queries {
+            for hit in tree.within_radius(&[position.x, position.y], QUERY_RADIUS) {
+                black_box(hit.entity);
+            }
+        }
+        query += start.elapsed();
+    }
+
+    (build / ITERATIONS, query / ITERATIONS)
+}
+
+fn bench_spatial_grid(enemies: &[Vec2], queries: &[Vec2]) -> (Duration, Duration) {
+    let mut grid = SpatialGrid::new(SPATIAL_GRID_CELL_SIZE);
+    let mut build = Duration::ZERO;
+    let mut query = Duration::ZERO;
+
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        grid.clear();
+        for (i, position) in enemies.iter().enumerate() {
+            grid.insert(Entity::from_raw(i as u32), *position);
+        }
+        build += start.elapsed();
+
+        let start = Instant::now();
+        for position in queries {
+            for (entity, _) in grid.within_radius(*position, QUERY_RADIUS) {
+                black_box(entity);
+            }
+        }
+        query += start.elapsed();
+    }
+
+    (build / ITERATIONS, query / ITERATIONS)
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    println!(
+        "{:>8} | {:>14} {:>14} | {:>14} {:>14}",
+        "enemies", "kd build", "kd query", "grid build", "grid query"
+    );
+
+    for count in ENEMY_COUNTS {
+        let enemies = random_positions(&mut rng, count);
+        let queries = random_positions(&mut rng, NUM_QUERIES);
+
+        let (kd_build, kd_query) = bench_kd_tree(&enemies, &queries);
+        let (grid_build, grid_query) = bench_spatial_grid(&enemies, &queries);
+
+        println!(
+            "{count:>8} | {kd_build:>14.2?} {kd_query:>14.2?} | {grid_build:>14.2?} {grid_query:>14.2?}"
+        );
+    }
+}
@@ -60,14 +60,17 @@ fn animate_player(
     }
 }
 
-fn animate_enemy(mut enemy_query: Query<(&mut TextureAtlas, &AnimationTimer), With<Enemy>>) {
+fn animate_enemy(mut enemy_query: Query<(&mut TextureAtlas, &Enemy, &AnimationTimer)>) {
     if enemy_query.is_empty() {
         return;
     }
 
-    for (mut texture_atlas, timer) in enemy_query.iter_mut() {
+    for (mut texture_atlas, enemy, timer) in enemy_query.iter_mut() {
         if timer.just_finished() {
-            texture_atlas.index = 12 + (texture_atlas.index + 1) % (SPRITE_SHEET_WIDTH / 4)
+            let archetype = enemy.kind.archetype();
+            let frame = texture_atlas.index.saturating_sub(archetype.sprite_index) + 1;
+
+            texture_atlas.index = archetype.sprite_index + frame % archetype.num_frames
         }
     }
 }
@@ -102,17 +105,9 @@ fn flip_enemy_sprite_x(
     }
 }
 
-fn flip_weapon_sprite_y(
-    cursor_position: Res<CursorPosition>,
-    mut weapon_query: Query<(&mut Sprite, &Transform), With<Weapon>>,
-) {
-    if weapon_query.is_empty() {
-        return;
-    }
-
-    let (mut sprite, transform) = weapon_query.single_mut();
-
-    if let Some(cursor_position) = cursor_position.0 {
-        sprite.flip_y = cursor_position.x < transform.translation.x;
+// Keeps the sprites upright while pointing to the left
+fn flip_weapon_sprite_y(mut weapon_query: Query<(&mut Sprite, &Transform), With<Weapon>>) {
+    for (mut sprite, transform) in weapon_query.iter_mut() {
+        sprite.flip_y = transform.local_x().x < 0.0;
     }
 }
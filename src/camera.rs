@@ -1,17 +1,23 @@
-use bevy::{math::vec3, prelude::*};
+use bevy::{
+    math::{vec2, vec3},
+    prelude::*,
+};
 use bevy_pancam::{PanCam, PanCamPlugin};
 
-use crate::{player::Player, state::GameState};
+use crate::{constants::*, player::Player, state::GameState, world::WorldBounds};
 
 pub struct FollowCameraPlugin;
 
 impl Plugin for FollowCameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(PanCamPlugin::default())
+        app.add_plugins(PanCamPlugin)
             .add_systems(OnEnter(GameState::Loading), setup_camera)
             .add_systems(
                 Update,
-                update_camera_position.run_if(in_state(GameState::Playing)),
+                (
+                    update_camera_bounds.run_if(resource_changed::<WorldBounds>),
+                    update_camera_position.run_if(in_state(GameState::Playing)),
+                ),
             );
     }
 }
@@ -22,18 +28,60 @@ fn setup_camera(mut commands: Commands) {
         .insert(PanCam::default());
 }
 
+/// Area the camera may show, the world and its border.
+fn visible_area(bounds: &WorldBounds) -> Rect {
+    bounds.0.inflate(WORLD_BORDER_THICKNESS)
+}
+
+// Also caps how far the camera can zoom out
+fn update_camera_bounds(bounds: Res<WorldBounds>, mut camera_query: Query<&mut PanCam>) {
+    let area = visible_area(&bounds);
+
+    for mut pancam in camera_query.iter_mut() {
+        pancam.min_x = Some(area.min.x);
+        pancam.max_x = Some(area.max.x);
+        pancam.min_y = Some(area.min.y);
+        pancam.max_y = Some(area.max.y);
+    }
+}
+
 fn update_camera_position(
+    time: Res<Time>,
+    bounds: Res<WorldBounds>,
     player_query: Query<&Transform, With<Player>>,
-    mut camera_query: Query<&mut Transform, (With<Camera>, Without<Player>)>,
+    mut camera_query: Query<
+        (&mut Transform, &OrthographicProjection),
+        (With<Camera>, Without<Player>),
+    >,
 ) {
     if camera_query.is_empty() || player_query.is_empty() {
         return;
     }
 
-    let mut camera_transform = camera_query.single_mut();
+    let (mut camera_transform, projection) = camera_query.single_mut();
     let player_transform = player_query.single().translation;
 
+    // Stop at the edges so nothing outside the world comes into view
+    let area = visible_area(&bounds);
+    let half_size = projection.area.half_size();
+    let target = vec2(
+        clamp_to_edges(player_transform.x, area.min.x, area.max.x, half_size.x),
+        clamp_to_edges(player_transform.y, area.min.y, area.max.y, half_size.y),
+    );
+
+    // Exponential smoothing so the camera catches up at the same rate regardless of FPS
+    let t = 1.0 - (-CAMERA_FOLLOW_RATE * time.delta_seconds()).exp();
+
     camera_transform.translation = camera_transform
         .translation
-        .lerp(vec3(player_transform.x, player_transform.y, 0.0), 0.1);
+        .lerp(vec3(target.x, target.y, 0.0), t);
+}
+
+/// Keeps a view of `half_size` within `min..max`, centered when it doesn't fit.
+fn clamp_to_edges(value: f32, min: f32, max: f32, half_size: f32) -> f32 {
+    if max - min <= half_size * 2.0 {
+        return (min + max) / 2.0;
+    }
+
+    value.clamp(min + half_size, max - half_size)
 }
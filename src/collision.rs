@@ -1,115 +1,500 @@
-use std::time::Duration;
-
-use bevy::{prelude::*, time::common_conditions::on_timer};
-use kd_tree::{KdPoint, KdTree};
+use bevy::prelude::*;
 
 use crate::{
     constants::*,
-    enemy::Enemy,
+    enemy::{Enemy, EnemyProjectile, EnemyType},
+    pickup::{Pickup, PickupCollectedEvent},
     player::{Player, PlayerEnemyCollisionEvent},
+    spatial::SpatialGrid,
     state::GameState,
-    weapon::Projectile,
+    stats::{Stat, Stats},
+    weapon::{Projectile, ProjectileHitEvent},
+    world::WorldBounds,
 };
 
 pub struct CollisionPlugin;
 
 impl Plugin for CollisionPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(EnemyKdTree::default()).add_systems(
-            Update,
-            (
-                handle_projectile_enemy_collision,
-                handle_player_enemy_collision,
-                update_enemy_kd_tree.run_if(on_timer(Duration::from_secs_f32(KD_TREE_UPDATE_RATE))),
-            )
-                .run_if(in_state(GameState::Playing)),
-        );
+        app.init_resource::<EnemySpatialIndex>()
+            .init_resource::<ObstacleIndex>()
+            .init_resource::<PickupIndex>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    update_obstacle_index,
+                    resolve_obstacle_collisions,
+                    handle_projectile_obstacle_collision,
+                    confine_to_world_bounds,
+                    despawn_projectiles_outside_world,
+                    update_enemy_spatial_index,
+                    update_pickup_index,
+                    handle_projectile_enemy_collision,
+                    handle_player_enemy_collision,
+                    handle_enemy_projectile_player_collision,
+                    handle_player_pickup_collision,
+                )
+                    .chain()
+                    .in_set(CollisionSet)
+                    .run_if(in_state(GameState::Playing)),
+            );
     }
 }
 
-#[derive(Component)]
-pub struct Collidable {
-    position: Vec2,
-    entity: Entity,
+/// Collision detection, runs after everything that moves entities in `FixedUpdate`.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CollisionSet;
+
+/// Hitbox of an entity, centered on its translation.
+#[derive(Component, Debug, Clone, Copy)]
+pub enum Collider {
+    Circle { radius: f32 },
+    Aabb { half_extents: Vec2 },
 }
 
-impl KdPoint for Collidable {
-    type Scalar = f32;
-    type Dim = typenum::U2;
+impl Collider {
+    /// Circle sized in sprite pixels, scaled like the rendered sprite.
+    pub fn sprite_circle(radius: f32) -> Self {
+        Self::Circle {
+            radius: radius * SPRITE_SCALE_FACTOR,
+        }
+    }
+
+    /// Box sized in sprite pixels, scaled like the rendered sprite.
+    pub fn sprite_aabb(width: f32, height: f32) -> Self {
+        Self::Aabb {
+            half_extents: Vec2::new(width, height) * SPRITE_SCALE_FACTOR / 2.0,
+        }
+    }
+
+    /// Half size of the smallest box containing the shape.
+    pub fn half_extents(&self) -> Vec2 {
+        match self {
+            Collider::Circle { radius } => Vec2::splat(*radius),
+            Collider::Aabb { half_extents } => *half_extents,
+        }
+    }
+
+    /// Radius of the smallest circle containing the shape.
+    pub fn bounding_radius(&self) -> f32 {
+        match self {
+            Collider::Circle { radius } => *radius,
+            Collider::Aabb { half_extents } => half_extents.length(),
+        }
+    }
+
+    pub fn intersects(&self, position: Vec2, other: &Collider, other_position: Vec2) -> bool {
+        match (self, other) {
+            (Collider::Circle { radius: a }, Collider::Circle { radius: b }) => {
+                position.distance_squared(other_position) <= (a + b) * (a + b)
+            }
+            (Collider::Circle { radius }, Collider::Aabb { half_extents }) => {
+                circle_intersects_aabb(position, *radius, other_position, *half_extents)
+            }
+            (Collider::Aabb { half_extents }, Collider::Circle { radius }) => {
+                circle_intersects_aabb(other_position, *radius, position, *half_extents)
+            }
+            (Collider::Aabb { half_extents: a }, Collider::Aabb { half_extents: b }) => {
+                let offset = (position - other_position).abs();
 
-    fn at(&self, i: usize) -> Self::Scalar {
-        if i == 0 {
-            return self.position.x;
+                offset.x <= a.x + b.x && offset.y <= a.y + b.y
+            }
         }
+    }
+}
+
+impl Collider {
+    /// Smallest offset moving this shape out of `other`, if they overlap.
+    pub fn penetration(
+        &self,
+        position: Vec2,
+        other: &Collider,
+        other_position: Vec2,
+    ) -> Option<Vec2> {
+        match (self, other) {
+            (Collider::Circle { radius: a }, Collider::Circle { radius: b }) => {
+                let offset = position - other_position;
+                let overlap = a + b - offset.length();
+
+                (overlap > 0.0).then(|| offset.try_normalize().unwrap_or(Vec2::X) * overlap)
+            }
+            (Collider::Circle { radius }, Collider::Aabb { half_extents }) => {
+                circle_aabb_penetration(position, *radius, other_position, *half_extents)
+            }
+            (Collider::Aabb { half_extents }, Collider::Circle { radius }) => {
+                circle_aabb_penetration(other_position, *radius, position, *half_extents)
+                    .map(|offset| -offset)
+            }
+            (Collider::Aabb { half_extents: a }, Collider::Aabb { half_extents: b }) => {
+                let offset = position - other_position;
+                let overlap = *a + *b - offset.abs();
 
-        self.position.y
+                if overlap.x <= 0.0 || overlap.y <= 0.0 {
+                    return None;
+                }
+
+                let sign = Vec2::select(offset.cmpge(Vec2::ZERO), Vec2::ONE, Vec2::NEG_ONE);
+
+                Some(if overlap.x < overlap.y {
+                    Vec2::new(overlap.x * sign.x, 0.0)
+                } else {
+                    Vec2::new(0.0, overlap.y * sign.y)
+                })
+            }
+        }
     }
 }
 
+fn circle_aabb_penetration(
+    center: Vec2,
+    radius: f32,
+    box_center: Vec2,
+    half_extents: Vec2,
+) -> Option<Vec2> {
+    let closest = center.clamp(box_center - half_extents, box_center + half_extents);
+    let offset = center - closest;
+    let distance = offset.length();
+
+    if distance > 0.0 {
+        return (distance < radius).then(|| offset / distance * (radius - distance));
+    }
+
+    // The center is inside the box, push out through the nearest face
+    let local = center - box_center;
+    let depth = half_extents - local.abs();
+    let sign = Vec2::select(local.cmpge(Vec2::ZERO), Vec2::ONE, Vec2::NEG_ONE);
+
+    Some(if depth.x < depth.y {
+        Vec2::new((depth.x + radius) * sign.x, 0.0)
+    } else {
+        Vec2::new(0.0, (depth.y + radius) * sign.y)
+    })
+}
+
+fn circle_intersects_aabb(center: Vec2, radius: f32, box_center: Vec2, half_extents: Vec2) -> bool {
+    let closest = center.clamp(box_center - half_extents, box_center + half_extents);
+
+    center.distance_squared(closest) <= radius * radius
+}
+
 #[derive(Resource)]
-struct EnemyKdTree(KdTree<Collidable>);
+pub struct EnemySpatialIndex(pub SpatialGrid);
 
-impl Default for EnemyKdTree {
+impl Default for EnemySpatialIndex {
     fn default() -> Self {
-        Self(KdTree::build_by_ordered_float(vec![]))
+        Self(SpatialGrid::new(SPATIAL_GRID_CELL_SIZE))
+    }
+}
+
+/// Static solid entity that blocks movement and steers enemies away.
+#[derive(Component)]
+pub struct Obstacle;
+
+#[derive(Resource)]
+pub struct ObstacleIndex(pub SpatialGrid);
+
+impl Default for ObstacleIndex {
+    fn default() -> Self {
+        Self(SpatialGrid::new(SPATIAL_GRID_CELL_SIZE))
+    }
+}
+
+#[derive(Resource)]
+pub struct PickupIndex(pub SpatialGrid);
+
+impl Default for PickupIndex {
+    fn default() -> Self {
+        Self(SpatialGrid::new(SPATIAL_GRID_CELL_SIZE))
+    }
+}
+
+/// Largest bounding radius of an obstacle, solid tiles are the only obstacles.
+fn max_obstacle_radius() -> f32 {
+    Vec2::splat(WORLD_TILE_SIZE / 2.0).length()
+}
+
+fn resolve_obstacle_collisions(
+    index: Res<ObstacleIndex>,
+    obstacle_query: Query<&Collider, With<Obstacle>>,
+    mut query: Query<(&mut Transform, &Collider), Or<(With<Player>, With<Enemy>)>>,
+) {
+    if index.0.is_empty() {
+        return;
+    }
+
+    for (mut transform, collider) in query.iter_mut() {
+        let mut position = transform.translation.truncate();
+        let query_radius = collider.bounding_radius() + max_obstacle_radius();
+
+        // Push out of each overlapping obstacle in turn, which slides along walls
+        for (e, obstacle_position) in index.0.within_radius(position, query_radius) {
+            let Ok(obstacle_collider) = obstacle_query.get(e) else {
+                continue;
+            };
+
+            if let Some(offset) =
+                collider.penetration(position, obstacle_collider, obstacle_position)
+            {
+                position += offset;
+            }
+        }
+
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
     }
 }
 
-fn update_enemy_kd_tree(
-    mut tree: ResMut<EnemyKdTree>,
+fn handle_projectile_obstacle_collision(
+    mut commands: Commands,
+    index: Res<ObstacleIndex>,
+    obstacle_query: Query<&Collider, With<Obstacle>>,
+    projectile_query: Query<
+        (&Transform, &Collider, Entity),
+        Or<(With<Projectile>, With<EnemyProjectile>)>,
+    >,
+) {
+    if index.0.is_empty() {
+        return;
+    }
+
+    for (transform, collider, entity) in projectile_query.iter() {
+        let position = transform.translation.truncate();
+        let query_radius = collider.bounding_radius() + max_obstacle_radius();
+
+        let blocked =
+            index
+                .0
+                .within_radius(position, query_radius)
+                .any(|(e, obstacle_position)| {
+                    obstacle_query.get(e).is_ok_and(|obstacle_collider| {
+                        collider.intersects(position, obstacle_collider, obstacle_position)
+                    })
+                });
+
+        if blocked {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn confine_to_world_bounds(
+    bounds: Res<WorldBounds>,
+    mut query: Query<(&mut Transform, &Collider), Or<(With<Player>, With<Enemy>)>>,
+) {
+    for (mut transform, collider) in query.iter_mut() {
+        let position = bounds.clamp(transform.translation.truncate(), collider.half_extents());
+
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+fn despawn_projectiles_outside_world(
+    mut commands: Commands,
+    bounds: Res<WorldBounds>,
+    projectile_query: Query<(&Transform, Entity), Or<(With<Projectile>, With<EnemyProjectile>)>>,
+) {
+    for (transform, entity) in projectile_query.iter() {
+        if !bounds.0.contains(transform.translation.truncate()) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn update_obstacle_index(
+    mut index: ResMut<ObstacleIndex>,
+    obstacle_query: Query<(&Transform, Entity), With<Obstacle>>,
+    added_query: Query<(), Added<Obstacle>>,
+    mut removed: RemovedComponents<Obstacle>,
+) {
+    // Obstacles don't move, only rebuild when the set changes
+    if added_query.is_empty() && removed.read().next().is_none() {
+        return;
+    }
+
+    index.0.clear();
+
+    for (t, e) in obstacle_query.iter() {
+        index.0.insert(e, t.translation.truncate());
+    }
+}
+
+fn update_enemy_spatial_index(
+    mut index: ResMut<EnemySpatialIndex>,
     enemy_query: Query<(&Transform, Entity), With<Enemy>>,
 ) {
-    let mut items = Vec::new();
+    index.0.clear();
 
     for (t, e) in enemy_query.iter() {
-        items.push(Collidable {
-            position: t.translation.truncate(),
-            entity: e,
-        })
+        index.0.insert(e, t.translation.truncate());
+    }
+}
+
+fn update_pickup_index(
+    mut index: ResMut<PickupIndex>,
+    pickup_query: Query<(&Transform, Entity), With<Pickup>>,
+    added_query: Query<(), Added<Pickup>>,
+    mut removed: RemovedComponents<Pickup>,
+) {
+    // Pickups lie still until collected, only rebuild when the set changes
+    if added_query.is_empty() && removed.read().next().is_none() {
+        return;
     }
 
-    tree.0 = KdTree::build_by_ordered_float(items);
+    index.0.clear();
+
+    for (t, e) in pickup_query.iter() {
+        index.0.insert(e, t.translation.truncate());
+    }
 }
 
 fn handle_projectile_enemy_collision(
-    projectile_query: Query<&Transform, With<Projectile>>,
-    tree: Res<EnemyKdTree>,
-    mut enemy_query: Query<&mut Enemy, With<Enemy>>,
+    mut commands: Commands,
+    mut projectile_query: Query<(&Transform, &Collider, &mut Projectile, Entity)>,
+    index: Res<EnemySpatialIndex>,
+    mut enemy_query: Query<(&Transform, &Collider, &mut Enemy), Without<Projectile>>,
+    mut events: EventWriter<ProjectileHitEvent>,
 ) {
     if projectile_query.is_empty() || enemy_query.is_empty() {
         return;
     }
 
-    for projectile_transform in projectile_query.iter() {
-        let projectile_position = projectile_transform.translation;
+    let max_enemy_radius = EnemyType::max_collider_radius();
+
+    for (projectile_transform, projectile_collider, mut projectile, projectile_entity) in
+        projectile_query.iter_mut()
+    {
+        let projectile_position = projectile_transform.translation.truncate();
+        let query_radius = projectile_collider.bounding_radius() + max_enemy_radius;
 
-        let enemies = tree
+        let mut targets = index
             .0
-            .within_radius(&[projectile_position.x, projectile_position.y], 50.0);
+            .within_radius(projectile_position, query_radius)
+            .filter(|(e, _)| !projectile.hits.contains(e))
+            .filter_map(|(e, enemy_position)| {
+                let (_, collider, enemy) = enemy_query.get(e).ok()?;
+
+                (enemy.health > 0.0
+                    && projectile_collider.intersects(
+                        projectile_position,
+                        collider,
+                        enemy_position,
+                    ))
+                .then_some((projectile_position.distance(enemy_position), e))
+            })
+            .collect::<Vec<_>>();
+
+        // Resolve the closest enemies first so pierce consumes them in travel order
+        targets.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        for (_, target) in targets {
+            let Ok((_, _, mut enemy)) = enemy_query.get_mut(target) else {
+                continue;
+            };
 
-        for e in enemies {
-            if let Ok(mut enemy) = enemy_query.get_mut(e.entity) {
-                enemy.health -= PROJECTILE_DAMAGE;
+            enemy.health -= projectile.damage;
+            projectile.hits.push(target);
+
+            events.send(ProjectileHitEvent {
+                projectile: projectile_entity,
+                target,
+                damage: projectile.damage,
+                position: projectile_position,
+            });
+
+            if projectile.pierce == 0 {
+                commands.entity(projectile_entity).despawn();
+                break;
             }
+
+            projectile.pierce -= 1;
         }
     }
 }
 
 fn handle_player_enemy_collision(
-    player_query: Query<&Transform, With<Player>>,
-    tree: Res<EnemyKdTree>,
+    player_query: Query<(&Transform, &Collider), With<Player>>,
+    index: Res<EnemySpatialIndex>,
+    enemy_query: Query<(&Transform, &Collider, &Stats), (With<Enemy>, Without<Player>)>,
     mut events: EventWriter<PlayerEnemyCollisionEvent>,
 ) {
     if player_query.is_empty() {
         return;
     }
 
-    let player_position = player_query.single().translation;
-    let enemies = tree
-        .0
-        .within_radius(&[player_position.x, player_position.y], 50.0);
+    let (player_transform, player_collider) = player_query.single();
+    let player_position = player_transform.translation.truncate();
+    let query_radius = player_collider.bounding_radius() + EnemyType::max_collider_radius();
+
+    for (e, _) in index.0.within_radius(player_position, query_radius) {
+        let Ok((transform, collider, stats)) = enemy_query.get(e) else {
+            continue;
+        };
 
-    for _ in enemies.iter() {
-        events.send(PlayerEnemyCollisionEvent);
+        let enemy_position = transform.translation.truncate();
+
+        if player_collider.intersects(player_position, collider, enemy_position) {
+            events.send(PlayerEnemyCollisionEvent {
+                attacker: e,
+                attacker_position: enemy_position,
+                damage: stats.get(Stat::Damage),
+            });
+        }
+    }
+}
+
+fn handle_enemy_projectile_player_collision(
+    mut commands: Commands,
+    player_query: Query<(&Transform, &Collider), With<Player>>,
+    projectile_query: Query<(&Transform, &Collider, &EnemyProjectile, Entity), Without<Player>>,
+    mut events: EventWriter<PlayerEnemyCollisionEvent>,
+) {
+    if player_query.is_empty() {
+        return;
+    }
+
+    let (player_transform, player_collider) = player_query.single();
+    let player_position = player_transform.translation.truncate();
+
+    for (transform, collider, projectile, entity) in projectile_query.iter() {
+        let projectile_position = transform.translation.truncate();
+
+        if player_collider.intersects(player_position, collider, projectile_position) {
+            events.send(PlayerEnemyCollisionEvent {
+                attacker: entity,
+                attacker_position: projectile_position,
+                damage: projectile.damage,
+            });
+
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn handle_player_pickup_collision(
+    player_query: Query<(&Transform, &Collider), With<Player>>,
+    index: Res<PickupIndex>,
+    pickup_query: Query<(&Transform, &Collider, &Pickup), Without<Player>>,
+    mut events: EventWriter<PickupCollectedEvent>,
+) {
+    let Ok((player_transform, player_collider)) = player_query.get_single() else {
+        return;
+    };
+
+    let player_position = player_transform.translation.truncate();
+    let query_radius = player_collider.bounding_radius()
+        + Collider::sprite_circle(PICKUP_COLLIDER_RADIUS).bounding_radius();
+
+    for (e, _) in index.0.within_radius(player_position, query_radius) {
+        let Ok((transform, collider, pickup)) = pickup_query.get(e) else {
+            continue;
+        };
+
+        if player_collider.intersects(player_position, collider, transform.translation.truncate()) {
+            events.send(PickupCollectedEvent {
+                pickup: e,
+                kind: pickup.0,
+            });
+        }
     }
 }
@@ -4,6 +4,13 @@ pub const WINDOW_HEIGHT: f32 = 700.0;
 
 pub const WINDOW_BG_COLOR: (u8, u8, u8) = (197, 204, 184);
 
+// Simulation
+pub const SIMULATION_HZ: f64 = 60.0;
+pub const HEADLESS_DEFAULT_TICKS: u32 = 60 * 60 * 5;
+pub const REPLAY_MAX_TICKS: usize = 4 * 60 * 60 * SIMULATION_HZ as usize;
+pub const CACHE_DIRECTORY: &str = "shooter2d";
+pub const LAST_REPLAY_FILE: &str = "last_run.replay";
+
 // Spritesheet
 pub const SPRITE_SHEET_PATH: &str = "assets.png";
 pub const SPRITE_SHEET_WIDTH: usize = 8;
@@ -11,6 +18,12 @@ pub const SPRITE_SHEET_HEIGTH: usize = 8;
 
 pub const SPRITE_SCALE_FACTOR: f32 = 3.0;
 
+// Fonts
+pub const FONT_PATH: &str = "monogram.ttf";
+
+// Camera
+pub const CAMERA_FOLLOW_RATE: f32 = 6.0;
+
 // Tiles
 pub const TILE_WIDTH: usize = 16;
 pub const TILE_HEIGHT: usize = 16;
@@ -18,23 +31,96 @@ pub const TILE_HEIGHT: usize = 16;
 // World
 pub const WORLD_W: f32 = 3000.0;
 pub const WORLD_H: f32 = 2500.0;
+pub const WORLD_TILE_SIZE: f32 = TILE_WIDTH as f32 * SPRITE_SCALE_FACTOR;
 pub const NUM_WORLD_DECORATIONS: usize = 1000;
+pub const NUM_WORLD_GROUND_TILES: usize = 1500;
+pub const NUM_WORLD_SOLID_CLUSTERS: usize = 60;
+pub const WORLD_SOLID_CLUSTER_SIZE: usize = 6;
+pub const WORLD_SPAWN_CLEARANCE: f32 = 300.0;
+pub const DECORATION_TILES: [usize; 3] = [24, 25, 56];
+pub const GROUND_TILES: [usize; 1] = [56];
+pub const SOLID_TILES: [usize; 3] = [57, 58, 59];
+pub const WORLD_BORDER_THICKNESS: f32 = 48.0;
+pub const WORLD_BORDER_COLOR: (u8, u8, u8) = (92, 99, 82);
+
+// Maps
+pub const MAP_PATHS: [&str; 2] = ["maps/courtyard.map.ron", "maps/graveyard.map.ron"];
 
 // Player
 pub const PLAYER_HEALTH: f32 = 100.0;
-pub const PLAYER_SPEED: f32 = 2.0;
+pub const PLAYER_SPEED: f32 = 120.0;
+pub const PLAYER_INVULNERABILITY_DURATION: f32 = 0.75;
+pub const PLAYER_BLINK_INTERVAL: f32 = 0.1;
+pub const PLAYER_KNOCKBACK_IMPULSE: f32 = 600.0;
+pub const PLAYER_KNOCKBACK_DECAY: f32 = 10.0;
 
-// Projectile
-pub const PROJECTILE_SPAWN_INTERVAL: f32 = 0.1;
-pub const PROJECTILE_SPEED: f32 = 15.0;
-pub const PROJECTILE_DAMAGE: f32 = 50.0;
+// Weapon
+/// Held weapons the player starts with, in slot order.
+pub const PLAYER_STARTING_WEAPONS: [&str; 3] = [
+    "weapons/shotgun.weapon.ron",
+    "weapons/smg.weapon.ron",
+    "weapons/rifle.weapon.ron",
+];
+/// Weapon gained with every orbiting weapon upgrade.
+pub const ORBITING_WEAPON: &str = "weapons/turret.weapon.ron";
+pub const WEAPON_ORBIT_RADIUS: f32 = 60.0;
+/// Radians per second.
+pub const WEAPON_ORBIT_SPEED: f32 = 2.0;
 
 // Enemy
 pub const MAX_NUM_ENEMIES: usize = 500;
 pub const ENEMY_SPAWN_INTERVAL: f32 = 1.0;
-pub const ENEMY_HEALTH: f32 = 100.0;
-pub const ENEMY_SPEED: f32 = 1.0;
-pub const ENEMY_DAMAGE: f32 = 1.0;
+pub const ENEMY_SPAWN_ATTEMPTS: usize = 16;
+pub const ENEMY_SPAWN_MIN_DISTANCE: f32 = 1000.0;
+pub const ENEMY_SPAWN_MAX_DISTANCE: f32 = 5000.0;
+pub const ENEMY_SPAWN_BATCH_SIZE: usize = 10;
+
+// Waves
+pub const WAVE_FIRST_DELAY: f32 = 2.0;
+pub const WAVE_REST_DURATION: f32 = 5.0;
+pub const WAVE_BASE_BUDGET: f32 = 20.0;
+pub const WAVE_BUDGET_GROWTH: f32 = 1.25;
+pub const WAVE_TARGET_DURATION: f32 = 30.0;
+pub const BOSS_WAVE_INTERVAL: u32 = 5;
+
+// Steering
+pub const ENEMY_SEPARATION_RADIUS: f32 = 40.0;
+pub const ENEMY_ALIGNMENT_RADIUS: f32 = 80.0;
+pub const OBSTACLE_AVOIDANCE_RADIUS: f32 = 60.0;
+
+// Experience
+pub const XP_GEM_SPRITE: usize = 16;
+pub const XP_GEM_COLOR: (u8, u8, u8) = (70, 190, 255);
+pub const XP_GEM_SPEED: f32 = 420.0;
+pub const XP_MAGNET_RADIUS: f32 = 150.0;
+pub const XP_PICKUP_RADIUS: f32 = 24.0;
+pub const XP_BASE_REQUIREMENT: u32 = 5;
+pub const XP_REQUIREMENT_GROWTH: f32 = 1.4;
+
+// Upgrades
+pub const UPGRADE_CHOICES: usize = 3;
+pub const UPGRADE_DAMAGE_BONUS: f32 = 0.2;
+pub const UPGRADE_FIRE_RATE_BONUS: f32 = 0.15;
+pub const UPGRADE_SPEED_BONUS: f32 = 0.1;
+pub const UPGRADE_MAX_HEALTH_BONUS: f32 = 20.0;
+
+// Pickups
+pub const PICKUP_HEAL_SPRITE: usize = 10;
+pub const PICKUP_FIRE_RATE_BOOST_SPRITE: usize = 17;
+pub const PICKUP_BOMB_SPRITE: usize = 23;
+pub const PICKUP_MAGNET_SPRITE: usize = 22;
+pub const PICKUP_SCALE: f32 = 0.75;
+/// Hitbox radius in sprite pixels.
+pub const PICKUP_COLLIDER_RADIUS: f32 = 4.0;
+pub const PICKUP_HEAL_AMOUNT: f32 = 30.0;
+pub const PICKUP_FIRE_RATE_BOOST: f32 = 2.0;
+pub const PICKUP_FIRE_RATE_BOOST_DURATION: f32 = 8.0;
+pub const PICKUP_BOMB_RADIUS: f32 = 700.0;
+pub const PICKUP_BOMB_DAMAGE: f32 = 1000.0;
+
+// Navigation
+pub const NAVIGATION_TILES_PER_TICK: usize = 2048;
+pub const FLOW_FIELD_DEBUG_RADIUS: u32 = 12;
 
-// Kd Tree
-pub const KD_TREE_UPDATE_RATE: f32 = 0.2;
+// Spatial index
+pub const SPATIAL_GRID_CELL_SIZE: f32 = 64.0;
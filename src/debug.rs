@@ -0,0 +1,313 @@
+use bevy::{color::palettes::css, prelude::*};
+
+use crate::{
+    collision::{Collider, EnemySpatialIndex, Obstacle, ObstacleIndex},
+    constants::FLOW_FIELD_DEBUG_RADIUS,
+    enemy::{Enemy, EnemyProjectile, EnemyType},
+    navigation::FlowField,
+    player::Player,
+    state::GameState,
+    steering::Velocity,
+    weapon::{Projectile, ProjectileDirection},
+    world::GameEntity,
+    GlobalFont,
+};
+
+pub struct DebugOverlayPlugin;
+
+/// Developer overlay, F3 toggles it and F4-F9 toggle the individual layers.
+#[derive(Resource)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+    pub colliders: bool,
+    pub spatial_index: bool,
+    pub steering: bool,
+    pub projectile_paths: bool,
+    pub camera_target: bool,
+    pub flow_field: bool,
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            colliders: true,
+            spatial_index: true,
+            steering: true,
+            projectile_paths: true,
+            camera_target: true,
+            flow_field: false,
+        }
+    }
+}
+
+#[derive(Component)]
+struct EntityCountText;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlay>()
+            .add_systems(OnEnter(GameState::Bootstraping), spawn_entity_count_text)
+            .add_systems(
+                Update,
+                (
+                    handle_debug_overlay_input,
+                    update_entity_count_text,
+                    (
+                        draw_colliders.run_if(|overlay: Res<DebugOverlay>| overlay.colliders),
+                        draw_spatial_index
+                            .run_if(|overlay: Res<DebugOverlay>| overlay.spatial_index),
+                        draw_steering.run_if(|overlay: Res<DebugOverlay>| overlay.steering),
+                        draw_projectile_paths
+                            .run_if(|overlay: Res<DebugOverlay>| overlay.projectile_paths),
+                        draw_camera_target
+                            .run_if(|overlay: Res<DebugOverlay>| overlay.camera_target),
+                        draw_flow_field.run_if(|overlay: Res<DebugOverlay>| overlay.flow_field),
+                    )
+                        .run_if(|overlay: Res<DebugOverlay>| overlay.enabled),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn handle_debug_overlay_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+) {
+    let overlay = overlay.as_mut();
+    let toggles = [
+        (KeyCode::F3, &mut overlay.enabled),
+        (KeyCode::F4, &mut overlay.colliders),
+        (KeyCode::F5, &mut overlay.spatial_index),
+        (KeyCode::F6, &mut overlay.steering),
+        (KeyCode::F7, &mut overlay.projectile_paths),
+        (KeyCode::F8, &mut overlay.camera_target),
+        (KeyCode::F9, &mut overlay.flow_field),
+    ];
+
+    for (key, enabled) in toggles {
+        if keyboard_input.just_pressed(key) {
+            *enabled = !*enabled;
+        }
+    }
+}
+
+fn draw_collider(gizmos: &mut Gizmos, collider: &Collider, position: Vec2, color: Srgba) {
+    match collider {
+        Collider::Circle { radius } => {
+            gizmos.circle_2d(position, *radius, color);
+        }
+        Collider::Aabb { half_extents } => {
+            gizmos.rect_2d(position, 0.0, *half_extents * 2.0, color);
+        }
+    }
+}
+
+fn draw_colliders(
+    mut gizmos: Gizmos,
+    collider_query: Query<(
+        &Transform,
+        &Collider,
+        Has<Player>,
+        Has<Enemy>,
+        Has<Obstacle>,
+    )>,
+) {
+    for (transform, collider, is_player, is_enemy, is_obstacle) in collider_query.iter() {
+        let color = if is_player {
+            css::LIME
+        } else if is_enemy {
+            css::RED
+        } else if is_obstacle {
+            css::GRAY
+        } else {
+            css::YELLOW
+        };
+
+        draw_collider(
+            &mut gizmos,
+            collider,
+            transform.translation.truncate(),
+            color,
+        );
+    }
+}
+
+fn draw_spatial_index(
+    mut gizmos: Gizmos,
+    enemy_index: Res<EnemySpatialIndex>,
+    obstacle_index: Res<ObstacleIndex>,
+) {
+    for (index, color) in [
+        (&enemy_index.0, css::ORANGE),
+        (&obstacle_index.0, css::SLATE_GRAY),
+    ] {
+        let cell_size = index.cell_size();
+
+        for (cell, items) in index.cells() {
+            let center = (cell.as_vec2() + 0.5) * cell_size;
+
+            // Busier cells are drawn more opaque
+            let alpha = (items.len() as f32 / 8.0).clamp(0.2, 1.0);
+
+            gizmos.rect_2d(center, 0.0, Vec2::splat(cell_size), color.with_alpha(alpha));
+        }
+    }
+}
+
+fn draw_steering(mut gizmos: Gizmos, enemy_query: Query<(&Transform, &Velocity), With<Enemy>>) {
+    for (transform, velocity) in enemy_query.iter() {
+        let position = transform.translation.truncate();
+
+        // Half a second worth of movement
+        gizmos.arrow_2d(position, position + velocity.0 * 0.5, css::AQUA);
+    }
+}
+
+fn draw_projectile_paths(
+    mut gizmos: Gizmos,
+    projectile_query: Query<(&Transform, &Projectile, &ProjectileDirection)>,
+    enemy_projectile_query: Query<(&Transform, &EnemyProjectile)>,
+) {
+    for (transform, projectile, direction) in projectile_query.iter() {
+        let position = transform.translation.truncate();
+        let remaining = projectile.lifetime.remaining_secs();
+        let end =
+            position + direction.0.truncate().normalize_or_zero() * projectile.speed * remaining;
+
+        gizmos.line_2d(position, end, css::YELLOW.with_alpha(0.5));
+    }
+
+    for (transform, projectile) in enemy_projectile_query.iter() {
+        let position = transform.translation.truncate();
+        let end = position + projectile.velocity * projectile.lifetime.remaining_secs();
+
+        gizmos.line_2d(position, end, css::MAGENTA.with_alpha(0.5));
+    }
+}
+
+fn draw_camera_target(
+    mut gizmos: Gizmos,
+    player_query: Query<&Transform, With<Player>>,
+    camera_query: Query<&Transform, (With<Camera>, Without<Player>)>,
+) {
+    let (Ok(player), Ok(camera)) = (player_query.get_single(), camera_query.get_single()) else {
+        return;
+    };
+
+    let target = player.translation.truncate();
+    let camera = camera.translation.truncate();
+
+    gizmos.circle_2d(target, 8.0, css::FUCHSIA);
+    gizmos.line_2d(camera, target, css::FUCHSIA);
+    gizmos.rect_2d(camera, 0.0, Vec2::splat(12.0), css::FUCHSIA);
+}
+
+fn spawn_entity_count_text(mut commands: Commands, font: Res<GlobalFont>) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: font.0.clone(),
+                font_size: 25.0,
+                color: Color::BLACK,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            right: Val::Px(15.0),
+            top: Val::Px(120.0),
+            ..default()
+        }),
+        EntityCountText,
+        GameEntity,
+    ));
+}
+
+fn update_entity_count_text(
+    overlay: Res<DebugOverlay>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<EntityCountText>>,
+    entity_query: Query<(
+        Has<Player>,
+        Option<&Enemy>,
+        Has<Projectile>,
+        Has<EnemyProjectile>,
+        Has<Obstacle>,
+        Has<Collider>,
+    )>,
+) {
+    let Ok((mut text, mut visibility)) = text_query.get_single_mut() else {
+        return;
+    };
+
+    if !overlay.enabled {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+
+    *visibility = Visibility::Inherited;
+
+    let mut entities = 0;
+    let mut players = 0;
+    let mut enemies = [0; EnemyType::ALL.len()];
+    let mut projectiles = 0;
+    let mut enemy_projectiles = 0;
+    let mut obstacles = 0;
+    let mut colliders = 0;
+
+    for (is_player, enemy, is_projectile, is_enemy_projectile, is_obstacle, has_collider) in
+        entity_query.iter()
+    {
+        entities += 1;
+        players += is_player as usize;
+        projectiles += is_projectile as usize;
+        enemy_projectiles += is_enemy_projectile as usize;
+        obstacles += is_obstacle as usize;
+        colliders += has_collider as usize;
+
+        if let Some(enemy) = enemy {
+            enemies[enemy.kind as usize] += 1;
+        }
+    }
+
+    let mut lines = vec![
+        format!("Entities: {entities}"),
+        format!("Player: {players}"),
+        format!("Enemy: {}", enemies.iter().sum::<usize>()),
+    ];
+
+    for (kind, count) in EnemyType::ALL.iter().zip(enemies) {
+        lines.push(format!("  {kind:?}: {count}"));
+    }
+
+    lines.extend([
+        format!("Projectile: {projectiles}"),
+        format!("EnemyProjectile: {enemy_projectiles}"),
+        format!("Obstacle: {obstacles}"),
+        format!("Collider: {colliders}"),
+    ]);
+
+    text.sections[0].value = lines.join("\n");
+}
+
+fn draw_flow_field(mut gizmos: Gizmos, field: Res<FlowField>) {
+    let Some(target) = field.target() else {
+        return;
+    };
+
+    // The whole map is too many arrows, only draw the tiles around the player
+    let radius = UVec2::splat(FLOW_FIELD_DEBUG_RADIUS);
+    let min = target.saturating_sub(radius);
+    let max = (target + radius).min(field.size() - 1);
+
+    for y in min.y..=max.y {
+        for x in min.x..=max.x {
+            let center = field.tile_center(UVec2::new(x, y));
+
+            if let Some(direction) = field.direction(center) {
+                gizmos.arrow_2d(center, center + direction * 16.0, css::AQUA);
+            }
+        }
+    }
+}
@@ -1,115 +1,584 @@
-use std::{f32::consts::PI, time::Duration};
+use std::f32::consts::PI;
 
-use bevy::{math::vec3, prelude::*, time::common_conditions::on_timer};
-use rand::Rng;
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
 
-use crate::{animation::AnimationTimer, player::Player, state::GameState, world::GameEntity, *};
+use crate::{
+    animation::AnimationTimer,
+    collision::{Collider, EnemySpatialIndex, ObstacleIndex},
+    interpolation::InterpolatedTranslation,
+    navigation::FlowField,
+    player::{apply_knockback, Player, PlayerEnemyCollisionEvent},
+    schedule::GameplaySet,
+    state::GameState,
+    stats::{Stat, Stats},
+    steering::{alignment, obstacle_avoidance, separation, SteeringWeights, Velocity},
+    world::{GameEntity, WorldBounds},
+    *,
+};
 
 pub struct EnemyPlugin;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EnemyType {
+    Grunt,
+    Runner,
+    Tank,
+    Shooter,
+    Exploder,
+    Boss,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum EnemyBehaviour {
+    /// Walks straight at the player.
+    Chase,
+    /// Keeps its distance from the player and fires projectiles at it.
+    Ranged {
+        preferred_distance: f32,
+        fire_interval: f32,
+        projectile_speed: f32,
+        projectile_damage: f32,
+    },
+    /// Walks straight at the player and blows up once close enough.
+    Explode {
+        trigger_radius: f32,
+        blast_damage: f32,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EnemyArchetype {
+    pub health: f32,
+    /// Units per second.
+    pub speed: f32,
+    /// Damage dealt on contact with the player.
+    pub damage: f32,
+    pub sprite_index: usize,
+    pub num_frames: usize,
+    pub scale: f32,
+    /// Hitbox radius in sprite pixels, before `scale` is applied.
+    pub collider_radius: f32,
+    pub behaviour: EnemyBehaviour,
+    /// Relative chance of being picked by the wave director, zero for scripted-only enemies.
+    pub spawn_weight: u32,
+    /// Wave budget spent when spawning one of these.
+    pub spawn_cost: u32,
+    /// Experience dropped on death.
+    pub xp: u32,
+}
+
+impl EnemyType {
+    pub const ALL: [EnemyType; 6] = [
+        EnemyType::Grunt,
+        EnemyType::Runner,
+        EnemyType::Tank,
+        EnemyType::Shooter,
+        EnemyType::Exploder,
+        EnemyType::Boss,
+    ];
+
+    /// Largest hitbox of any enemy type, used to pad spatial queries.
+    pub fn max_collider_radius() -> f32 {
+        Self::ALL
+            .iter()
+            .map(|kind| kind.archetype().collider().bounding_radius())
+            .fold(0.0, f32::max)
+    }
+
+    pub fn archetype(&self) -> EnemyArchetype {
+        match self {
+            EnemyType::Grunt => EnemyArchetype {
+                health: 100.0,
+                speed: 60.0,
+                damage: 10.0,
+                sprite_index: 12,
+                num_frames: 2,
+                scale: 1.0,
+                collider_radius: 6.0,
+                behaviour: EnemyBehaviour::Chase,
+                spawn_weight: 50,
+                spawn_cost: 1,
+                xp: 1,
+            },
+            EnemyType::Runner => EnemyArchetype {
+                health: 50.0,
+                speed: 132.0,
+                damage: 5.0,
+                sprite_index: 8,
+                num_frames: 2,
+                scale: 1.0,
+                collider_radius: 5.0,
+                behaviour: EnemyBehaviour::Chase,
+                spawn_weight: 25,
+                spawn_cost: 1,
+                xp: 1,
+            },
+            EnemyType::Tank => EnemyArchetype {
+                health: 600.0,
+                speed: 30.0,
+                damage: 25.0,
+                sprite_index: 28,
+                num_frames: 2,
+                scale: 1.0,
+                collider_radius: 7.0,
+                behaviour: EnemyBehaviour::Chase,
+                spawn_weight: 8,
+                spawn_cost: 5,
+                xp: 5,
+            },
+            EnemyType::Shooter => EnemyArchetype {
+                health: 80.0,
+                speed: 48.0,
+                damage: 10.0,
+                sprite_index: 20,
+                num_frames: 2,
+                scale: 1.0,
+                collider_radius: 6.0,
+                behaviour: EnemyBehaviour::Ranged {
+                    preferred_distance: 400.0,
+                    fire_interval: 2.0,
+                    projectile_speed: 360.0,
+                    projectile_damage: 10.0,
+                },
+                spawn_weight: 10,
+                spawn_cost: 3,
+                xp: 3,
+            },
+            EnemyType::Exploder => EnemyArchetype {
+                health: 60.0,
+                speed: 96.0,
+                damage: 0.0,
+                sprite_index: 14,
+                num_frames: 2,
+                scale: 1.0,
+                collider_radius: 6.0,
+                behaviour: EnemyBehaviour::Explode {
+                    trigger_radius: 60.0,
+                    blast_damage: 25.0,
+                },
+                spawn_weight: 7,
+                spawn_cost: 2,
+                xp: 2,
+            },
+            EnemyType::Boss => EnemyArchetype {
+                health: 5000.0,
+                speed: 42.0,
+                damage: 40.0,
+                sprite_index: 30,
+                num_frames: 2,
+                scale: 2.5,
+                collider_radius: 7.0,
+                behaviour: EnemyBehaviour::Chase,
+                spawn_weight: 0,
+                spawn_cost: 0,
+                xp: 50,
+            },
+        }
+    }
+}
+
+impl EnemyArchetype {
+    pub fn collider(&self) -> Collider {
+        Collider::sprite_circle(self.collider_radius * self.scale)
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats::default()
+            .with_base(Stat::MaxHealth, self.health)
+            .with_base(Stat::Speed, self.speed)
+            .with_base(Stat::Damage, self.damage)
+    }
+}
+
 #[derive(Component)]
 pub struct Enemy {
     pub health: f32,
+    pub kind: EnemyType,
 }
 
-impl Default for Enemy {
-    fn default() -> Self {
+impl Enemy {
+    /// Starts at full health, with every max health modifier of `stats` applied.
+    pub fn new(kind: EnemyType, stats: &Stats) -> Self {
         Self {
-            health: ENEMY_HEALTH,
+            health: stats.get(Stat::MaxHealth),
+            kind,
         }
     }
 }
 
+impl Default for Enemy {
+    fn default() -> Self {
+        Self::new(EnemyType::Grunt, &EnemyType::Grunt.archetype().stats())
+    }
+}
+
+#[derive(Event, Clone)]
+pub struct EnemyKilledEvent {
+    pub entity: Entity,
+    pub kind: EnemyType,
+    pub position: Vec2,
+}
+
+#[derive(Component)]
+struct EnemyAttackTimer(Timer);
+
+#[derive(Component)]
+pub struct EnemyProjectile {
+    pub damage: f32,
+    pub velocity: Vec2,
+    pub lifetime: Timer,
+}
+
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                spawn_enemy_wave.run_if(on_timer(Duration::from_secs_f32(ENEMY_SPAWN_INTERVAL))),
-                update_enemy_transform,
-                despawn_dead_enemies,
-            )
-                .run_if(in_state(GameState::Playing)),
+        app.add_event::<EnemyKilledEvent>()
+            .init_resource::<SteeringWeights>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    // Steer towards where the player moved this tick
+                    (update_enemy_transform, update_enemy_projectiles)
+                        .chain()
+                        .in_set(GameplaySet::Movement)
+                        .after(apply_knockback),
+                    (handle_enemy_ranged_attacks, handle_enemy_explosions)
+                        .chain()
+                        .in_set(GameplaySet::Damage),
+                    despawn_dead_enemies.in_set(GameplaySet::Cleanup),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Somewhere in one of the spawn zones, or far enough from `pos` to be off screen without zones.
+pub fn get_random_spawn_position(
+    rng: &mut impl Rng,
+    bounds: &WorldBounds,
+    zones: &[Rect],
+    pos: Vec2,
+) -> (f32, f32) {
+    if let Some(zone) = zones.choose(rng) {
+        let position = Vec2::new(
+            rng.gen_range(zone.min.x..=zone.max.x),
+            rng.gen_range(zone.min.y..=zone.max.y),
         );
+        let position = bounds.clamp(position, Vec2::ZERO);
+
+        return (position.x, position.y);
+    }
+
+    for _ in 0..ENEMY_SPAWN_ATTEMPTS {
+        let direction = Vec2::from_angle(rng.gen_range(0.0..PI * 2.0));
+
+        // Only as far as the arena reaches in that direction, directions leaving it too soon
+        // are rolled again
+        let reach = distance_to_edge(&bounds.0, pos, direction).min(ENEMY_SPAWN_MAX_DISTANCE);
+
+        if reach >= ENEMY_SPAWN_MIN_DISTANCE {
+            let position = pos + direction * rng.gen_range(ENEMY_SPAWN_MIN_DISTANCE..=reach);
+            let position = bounds.clamp(position, Vec2::ZERO);
+
+            return (position.x, position.y);
+        }
+    }
+
+    // Deep in a corner every attempt can miss, fall back to the farthest corner of the arena
+    let corners = [
+        bounds.0.min,
+        bounds.0.max,
+        Vec2::new(bounds.0.min.x, bounds.0.max.y),
+        Vec2::new(bounds.0.max.x, bounds.0.min.y),
+    ];
+    let position = corners
+        .into_iter()
+        .max_by(|a, b| a.distance_squared(pos).total_cmp(&b.distance_squared(pos)))
+        .unwrap();
+
+    (position.x, position.y)
+}
+
+/// Distance from `origin` to the edge of `rect` along `direction`.
+fn distance_to_edge(rect: &Rect, origin: Vec2, direction: Vec2) -> f32 {
+    let axis = |origin: f32, direction: f32, min: f32, max: f32| {
+        if direction > 0.0 {
+            (max - origin) / direction
+        } else if direction < 0.0 {
+            (min - origin) / direction
+        } else {
+            f32::INFINITY
+        }
+    };
+
+    let x = axis(origin.x, direction.x, rect.min.x, rect.max.x);
+    let y = axis(origin.y, direction.y, rect.min.y, rect.max.y);
+
+    x.min(y)
+}
+
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    kind: EnemyType,
+    translation: Vec3,
+) -> Entity {
+    let archetype = kind.archetype();
+    let stats = archetype.stats();
+
+    let mut enemy = commands.spawn((
+        SpriteBundle {
+            texture: handle.image.clone().unwrap_or_default(),
+            transform: Transform::from_translation(translation)
+                .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR * archetype.scale)),
+            ..default()
+        },
+        TextureAtlas {
+            layout: handle.layout.clone().unwrap_or_default(),
+            index: archetype.sprite_index,
+        },
+        Enemy::new(kind, &stats),
+        archetype.collider(),
+        stats,
+        Velocity::default(),
+        InterpolatedTranslation::new(translation),
+        AnimationTimer(Timer::from_seconds(0.08, TimerMode::Repeating)),
+        GameEntity,
+    ));
+
+    if let EnemyBehaviour::Ranged { fire_interval, .. } = archetype.behaviour {
+        enemy.insert(EnemyAttackTimer(Timer::from_seconds(
+            fire_interval,
+            TimerMode::Repeating,
+        )));
     }
+
+    enemy.id()
 }
 
-fn get_random_spawn_position(pos: Vec2) -> (f32, f32) {
-    let mut rng = rand::thread_rng();
-    let angle = rng.gen_range(0.0..PI * 2.0);
-    let distance = rng.gen_range(1000.0..5000.0);
+#[allow(clippy::too_many_arguments)]
+fn update_enemy_transform(
+    time: Res<Time>,
+    weights: Res<SteeringWeights>,
+    enemy_index: Res<EnemySpatialIndex>,
+    obstacle_index: Res<ObstacleIndex>,
+    flow_field: Res<FlowField>,
+    player_query: Query<&Transform, With<Player>>,
+    mut enemy_query: Query<
+        (&mut Transform, &mut Velocity, &Enemy, &Stats, Entity),
+        Without<Player>,
+    >,
+    collider_query: Query<&Collider>,
+) {
+    if player_query.is_empty() || enemy_query.is_empty() {
+        return;
+    }
 
-    let offset_x = angle.cos() * distance;
-    let offset_y = angle.sin() * distance;
+    let player_position = player_query.single().translation.truncate();
+    let dt = time.delta_seconds();
+    let turn = 1.0 - (-weights.responsiveness * dt).exp();
+
+    let mut steered = Vec::with_capacity(enemy_query.iter().len());
+
+    for (transform, velocity, enemy, stats, entity) in enemy_query.iter() {
+        let archetype = enemy.kind.archetype();
+        let position = transform.translation.truncate();
+        let offset = player_position - position;
+        let dir = offset.normalize_or_zero();
+        // Route around walls when approaching, retreating heads straight away
+        let path = flow_field.direction(position).unwrap_or(dir);
+
+        let seek = match archetype.behaviour {
+            EnemyBehaviour::Chase | EnemyBehaviour::Explode { .. } => path,
+            EnemyBehaviour::Ranged {
+                preferred_distance, ..
+            } => {
+                let distance = offset.length();
 
-    let random_x = pos.x + offset_x;
-    let random_y = pos.y + offset_y;
+                if distance > preferred_distance {
+                    path
+                } else if distance < preferred_distance * 0.8 {
+                    -dir
+                } else {
+                    Vec2::ZERO
+                }
+            }
+        };
 
-    (random_x, random_y)
+        let separation = separation(
+            position,
+            ENEMY_SEPARATION_RADIUS,
+            enemy_index
+                .0
+                .within_radius(position, ENEMY_SEPARATION_RADIUS),
+            entity,
+        );
+        let alignment = alignment(
+            enemy_index
+                .0
+                .within_radius(position, ENEMY_ALIGNMENT_RADIUS)
+                .filter(|(e, _)| *e != entity)
+                .filter_map(|(e, _)| enemy_query.get(e).ok())
+                .map(|(_, velocity, ..)| velocity.0),
+        );
+        let avoidance = obstacle_avoidance(
+            position,
+            OBSTACLE_AVOIDANCE_RADIUS,
+            &obstacle_index.0,
+            |e| collider_query.get(e).ok().copied(),
+        );
+
+        let desired = (seek * weights.seek
+            + separation * weights.separation
+            + alignment * weights.alignment
+            + avoidance * weights.obstacle_avoidance)
+            .clamp_length_max(1.0)
+            * stats.get(Stat::Speed);
+
+        steered.push((entity, velocity.0.lerp(desired, turn)));
+    }
+
+    for (entity, new_velocity) in steered {
+        if let Ok((mut transform, mut velocity, ..)) = enemy_query.get_mut(entity) {
+            velocity.0 = new_velocity;
+            transform.translation += new_velocity.extend(0.0) * dt;
+        }
+    }
 }
 
-fn spawn_enemy_wave(
+fn handle_enemy_ranged_attacks(
     mut commands: Commands,
+    time: Res<Time>,
     handle: Res<GlobalTextureAtlas>,
     player_query: Query<&Transform, With<Player>>,
-    enemy_query: Query<&Transform, (With<Enemy>, Without<Player>)>,
+    mut enemy_query: Query<(&Transform, &Enemy, &Stats, &mut EnemyAttackTimer), Without<Player>>,
 ) {
-    let enemy_spawn_rate_per_second = 10;
-
-    let num_enemies = enemy_query.iter().len();
-    let enemy_spawn_count = (MAX_NUM_ENEMIES - num_enemies).min(enemy_spawn_rate_per_second);
-
-    if num_enemies >= MAX_NUM_ENEMIES || player_query.is_empty() {
+    if player_query.is_empty() || enemy_query.is_empty() {
         return;
     }
 
     let player_position = player_query.single().translation.truncate();
 
-    for _ in 0..enemy_spawn_count {
-        let (x, y) = get_random_spawn_position(player_position);
+    for (transform, enemy, stats, mut attack_timer) in enemy_query.iter_mut() {
+        let EnemyBehaviour::Ranged {
+            preferred_distance,
+            projectile_speed,
+            projectile_damage,
+            ..
+        } = enemy.kind.archetype().behaviour
+        else {
+            continue;
+        };
+
+        if !attack_timer.0.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let enemy_position = transform.translation.truncate();
+        let offset = player_position - enemy_position;
+
+        // Only shoot when the player is roughly within range
+        if offset.length() > preferred_distance * 1.5 {
+            continue;
+        }
+
+        let projectile_position = enemy_position.extend(10.0);
 
         commands.spawn((
             SpriteBundle {
-                texture: handle.image.clone().unwrap(),
-                transform: Transform::from_translation(vec3(x, y, 1.0))
+                texture: handle.image.clone().unwrap_or_default(),
+                sprite: Sprite {
+                    color: Color::srgb(1.0, 0.3, 0.3),
+                    ..default()
+                },
+                transform: Transform::from_translation(projectile_position)
                     .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
                 ..default()
             },
             TextureAtlas {
-                layout: handle.layout.clone().unwrap(),
-                index: 12,
+                layout: handle.layout.clone().unwrap_or_default(),
+                index: 16,
             },
-            Enemy::default(),
-            AnimationTimer(Timer::from_seconds(0.08, TimerMode::Repeating)),
+            EnemyProjectile {
+                damage: stats.apply(Stat::Damage, projectile_damage),
+                velocity: offset.normalize_or_zero() * projectile_speed,
+                lifetime: Timer::from_seconds(3.0, TimerMode::Once),
+            },
+            Collider::sprite_circle(2.5),
+            InterpolatedTranslation::new(projectile_position),
             GameEntity,
         ));
     }
 }
 
-fn update_enemy_transform(
+pub fn update_enemy_projectiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut projectile_query: Query<(&mut Transform, &mut EnemyProjectile, Entity)>,
+) {
+    for (mut transform, mut projectile, entity) in projectile_query.iter_mut() {
+        transform.translation += projectile.velocity.extend(0.0) * time.delta_seconds();
+
+        if projectile.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+pub fn handle_enemy_explosions(
+    mut commands: Commands,
     player_query: Query<&Transform, With<Player>>,
-    mut enemy_query: Query<&mut Transform, (With<Enemy>, Without<Player>)>,
+    enemy_query: Query<(&Transform, &Enemy, &Stats, Entity), Without<Player>>,
+    mut events: EventWriter<PlayerEnemyCollisionEvent>,
 ) {
     if player_query.is_empty() || enemy_query.is_empty() {
         return;
     }
 
-    let player_position = player_query.single().translation;
+    let player_position = player_query.single().translation.truncate();
+
+    for (transform, enemy, stats, entity) in enemy_query.iter() {
+        let EnemyBehaviour::Explode {
+            trigger_radius,
+            blast_damage,
+        } = enemy.kind.archetype().behaviour
+        else {
+            continue;
+        };
 
-    for mut transform in enemy_query.iter_mut() {
-        let dir = (player_position - transform.translation).normalize();
+        let enemy_position = transform.translation.truncate();
 
-        transform.translation += dir * ENEMY_SPEED;
+        if player_position.distance(enemy_position) > trigger_radius {
+            continue;
+        }
+
+        events.send(PlayerEnemyCollisionEvent {
+            attacker: entity,
+            attacker_position: enemy_position,
+            damage: stats.apply(Stat::Damage, blast_damage),
+        });
+
+        commands.entity(entity).despawn();
     }
 }
 
-fn despawn_dead_enemies(mut commands: Commands, enemy_query: Query<(&Enemy, Entity), With<Enemy>>) {
+pub fn despawn_dead_enemies(
+    mut commands: Commands,
+    enemy_query: Query<(&Transform, &Enemy, Entity)>,
+    mut events: EventWriter<EnemyKilledEvent>,
+) {
     if enemy_query.is_empty() {
         return;
     }
 
-    for (enemy, entity) in enemy_query.iter() {
+    for (transform, enemy, entity) in enemy_query.iter() {
         if enemy.health <= 0.0 {
+            events.send(EnemyKilledEvent {
+                entity,
+                kind: enemy.kind,
+                position: transform.translation.truncate(),
+            });
+
             commands.entity(entity).despawn()
         }
     }
@@ -0,0 +1,289 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+use crate::{
+    enemy::EnemyKilledEvent,
+    input::PlayerInput,
+    interpolation::InterpolatedTranslation,
+    player::{Health, Player},
+    rng::{GameRng, RngStream},
+    state::GameState,
+    stats::{Modifier, ModifierSource, Stat, Stats},
+    world::GameEntity,
+    *,
+};
+
+pub struct ExperiencePlugin;
+
+/// Experience dropped by a dead enemy, holding the points it is worth.
+#[derive(Component)]
+pub struct XpGem(pub u32);
+
+/// Flies to the player from any distance.
+#[derive(Component)]
+pub struct Magnetized;
+
+#[derive(Resource, Debug)]
+pub struct Experience {
+    pub level: u32,
+    /// Points collected towards the next level.
+    pub xp: u32,
+    /// Level-ups still waiting for an upgrade to be chosen.
+    pub pending_level_ups: u32,
+}
+
+impl Default for Experience {
+    fn default() -> Self {
+        Self {
+            level: 1,
+            xp: 0,
+            pending_level_ups: 0,
+        }
+    }
+}
+
+impl Experience {
+    pub fn xp_to_next_level(&self) -> u32 {
+        let growth = XP_REQUIREMENT_GROWTH.powi(self.level as i32 - 1);
+
+        (XP_BASE_REQUIREMENT as f32 * growth).round() as u32
+    }
+
+    /// Adds collected points, returns how many levels were gained.
+    pub fn add(&mut self, xp: u32) -> u32 {
+        let mut levels = 0;
+
+        self.xp += xp;
+
+        while self.xp >= self.xp_to_next_level() {
+            self.xp -= self.xp_to_next_level();
+            self.level += 1;
+            levels += 1;
+        }
+
+        self.pending_level_ups += levels;
+        levels
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Upgrade {
+    Damage,
+    FireRate,
+    Speed,
+    MaxHealth,
+    ExtraPellets,
+    OrbitingWeapon,
+}
+
+impl Upgrade {
+    pub const ALL: [Upgrade; 6] = [
+        Upgrade::Damage,
+        Upgrade::FireRate,
+        Upgrade::Speed,
+        Upgrade::MaxHealth,
+        Upgrade::ExtraPellets,
+        Upgrade::OrbitingWeapon,
+    ];
+
+    pub fn label(&self) -> String {
+        let percent = |bonus: f32| (bonus * 100.0).round();
+
+        match self {
+            Upgrade::Damage => format!("+{}% damage", percent(UPGRADE_DAMAGE_BONUS)),
+            Upgrade::FireRate => format!("+{}% fire rate", percent(UPGRADE_FIRE_RATE_BONUS)),
+            Upgrade::Speed => format!("+{}% speed", percent(UPGRADE_SPEED_BONUS)),
+            Upgrade::MaxHealth => format!("+{UPGRADE_MAX_HEALTH_BONUS:.0} max health"),
+            Upgrade::ExtraPellets => "+1 pellet".to_string(),
+            Upgrade::OrbitingWeapon => "+1 orbiting turret".to_string(),
+        }
+    }
+
+    /// Permanent stat modifier granted by the upgrade, stacking with earlier picks.
+    pub fn modifier(&self) -> Modifier {
+        let source = ModifierSource::Upgrade;
+
+        match self {
+            Upgrade::Damage => Modifier::multiply(Stat::Damage, 1.0 + UPGRADE_DAMAGE_BONUS, source),
+            Upgrade::FireRate => {
+                Modifier::multiply(Stat::FireRate, 1.0 + UPGRADE_FIRE_RATE_BONUS, source)
+            }
+            Upgrade::Speed => Modifier::multiply(Stat::Speed, 1.0 + UPGRADE_SPEED_BONUS, source),
+            Upgrade::MaxHealth => Modifier::add(Stat::MaxHealth, UPGRADE_MAX_HEALTH_BONUS, source),
+            Upgrade::ExtraPellets => Modifier::add(Stat::Pellets, 1.0, source),
+            Upgrade::OrbitingWeapon => Modifier::add(Stat::OrbitingWeapons, 1.0, source),
+        }
+    }
+}
+
+/// Upgrades offered for the oldest pending level-up, picked through `PlayerInput::upgrade`.
+#[derive(Resource, Default, Debug)]
+pub struct UpgradeChoices(pub Vec<Upgrade>);
+
+/// Choice made in the level-up menu, passed on with the input of the next tick.
+#[derive(Resource, Default)]
+pub struct UpgradeSelection(pub Option<u8>);
+
+impl Plugin for ExperiencePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Experience>()
+            .init_resource::<UpgradeChoices>()
+            .init_resource::<UpgradeSelection>()
+            .add_systems(OnEnter(GameState::Bootstraping), reset_experience)
+            .add_systems(
+                FixedPostUpdate,
+                (
+                    drop_xp_gems,
+                    attract_xp_gems,
+                    collect_xp_gems,
+                    choose_upgrade,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn reset_experience(
+    mut experience: ResMut<Experience>,
+    mut choices: ResMut<UpgradeChoices>,
+    mut selection: ResMut<UpgradeSelection>,
+) {
+    *experience = Experience::default();
+    choices.0.clear();
+    selection.0 = None;
+}
+
+fn drop_xp_gems(
+    mut commands: Commands,
+    handle: Res<GlobalTextureAtlas>,
+    mut events: EventReader<EnemyKilledEvent>,
+) {
+    for event in events.read() {
+        spawn_xp_gem(
+            &mut commands,
+            &handle,
+            event.kind.archetype().xp,
+            event.position,
+        );
+    }
+}
+
+pub fn spawn_xp_gem(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    xp: u32,
+    position: Vec2,
+) -> Entity {
+    let translation = position.extend(0.8);
+
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: handle.image.clone().unwrap_or_default(),
+                sprite: Sprite {
+                    color: Color::srgb_u8(XP_GEM_COLOR.0, XP_GEM_COLOR.1, XP_GEM_COLOR.2),
+                    ..default()
+                },
+                transform: Transform::from_translation(translation)
+                    .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+                ..default()
+            },
+            TextureAtlas {
+                layout: handle.layout.clone().unwrap_or_default(),
+                index: XP_GEM_SPRITE,
+            },
+            InterpolatedTranslation::new(translation),
+            XpGem(xp),
+            GameEntity,
+        ))
+        .id()
+}
+
+fn attract_xp_gems(
+    time: Res<Time>,
+    player_query: Query<&Transform, With<Player>>,
+    mut gem_query: Query<(&mut Transform, Has<Magnetized>), (With<XpGem>, Without<Player>)>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let player_position = player_transform.translation.truncate();
+    let step = XP_GEM_SPEED * time.delta_seconds();
+
+    for (mut transform, magnetized) in gem_query.iter_mut() {
+        let offset = player_position - transform.translation.truncate();
+
+        if magnetized || offset.length() <= XP_MAGNET_RADIUS {
+            transform.translation += offset.clamp_length_max(step).extend(0.0);
+        }
+    }
+}
+
+fn collect_xp_gems(
+    mut commands: Commands,
+    mut experience: ResMut<Experience>,
+    mut choices: ResMut<UpgradeChoices>,
+    mut rng: ResMut<GameRng>,
+    player_query: Query<&Transform, With<Player>>,
+    gem_query: Query<(&Transform, &XpGem, Entity)>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let player_position = player_transform.translation.truncate();
+
+    for (transform, gem, entity) in gem_query.iter() {
+        if transform.translation.truncate().distance(player_position) <= XP_PICKUP_RADIUS {
+            experience.add(gem.0);
+            commands.entity(entity).despawn();
+        }
+    }
+
+    if experience.pending_level_ups > 0 && choices.0.is_empty() {
+        choices.0 = roll_upgrades(&mut rng);
+    }
+}
+
+fn roll_upgrades(rng: &mut GameRng) -> Vec<Upgrade> {
+    Upgrade::ALL
+        .choose_multiple(rng.stream(RngStream::Upgrades), UPGRADE_CHOICES)
+        .copied()
+        .collect()
+}
+
+pub fn choose_upgrade(
+    input: Res<PlayerInput>,
+    mut experience: ResMut<Experience>,
+    mut choices: ResMut<UpgradeChoices>,
+    mut rng: ResMut<GameRng>,
+    mut player_query: Query<(&mut Stats, &mut Health), With<Player>>,
+) {
+    let Some(upgrade) = input
+        .upgrade
+        .and_then(|index| choices.0.get(index as usize).copied())
+    else {
+        return;
+    };
+
+    let Ok((mut stats, mut health)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    stats.add_modifier(upgrade.modifier());
+
+    // The extra max health comes filled
+    if upgrade == Upgrade::MaxHealth {
+        health.0 += UPGRADE_MAX_HEALTH_BONUS;
+    }
+
+    experience.pending_level_ups -= 1;
+
+    choices.0 = if experience.pending_level_ups > 0 {
+        roll_upgrades(&mut rng)
+    } else {
+        Vec::new()
+    };
+}
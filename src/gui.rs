@@ -1,26 +1,58 @@
 use bevy::{
     diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
     prelude::*,
+    window::{PrimaryWindow, WindowMode},
 };
 
 use crate::{
     enemy::Enemy,
+    experience::{UpgradeChoices, UpgradeSelection},
+    map::{MapDefinition, MapList, SelectedMap},
     player::{Health, Player},
-    state::GameState,
+    replay::ReplayMode,
+    rng::GameRng,
+    run_stats::RunStats,
+    state::{GameState, PlayState},
+    wave::{WaveCleared, WaveStarted},
     world::GameEntity,
+    GlobalFont,
 };
 
 pub struct GUIPlugin;
 
 impl Plugin for GUIPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Menu), spawn_menu)
+        app.init_resource::<ShowDebugText>()
+            .add_systems(OnEnter(GameState::Menu), spawn_menu)
             .add_systems(OnExit(GameState::Menu), despawn_menu)
-            .add_systems(Update, handle_menu_input.run_if(in_state(GameState::Menu)))
+            .add_systems(OnEnter(GameState::GameOver), spawn_game_over_screen)
+            .add_systems(OnExit(GameState::GameOver), despawn_game_over_screen)
+            .add_systems(OnEnter(PlayState::Paused), spawn_pause_menu)
+            .add_systems(OnExit(PlayState::Paused), despawn_pause_menu)
+            .add_systems(OnEnter(PlayState::Settings), spawn_settings_menu)
+            .add_systems(OnExit(PlayState::Settings), despawn_settings_menu)
+            .add_systems(OnEnter(PlayState::LevelUp), spawn_level_up_menu)
+            .add_systems(OnExit(PlayState::LevelUp), despawn_level_up_menu)
+            .add_systems(
+                Update,
+                (
+                    pause_for_level_up.run_if(in_state(PlayState::Running)),
+                    handle_upgrade_input.run_if(in_state(PlayState::LevelUp)),
+                ),
+            )
+            .add_systems(Update, handle_menu_input)
+            .add_systems(Update, update_map_label.run_if(in_state(GameState::Menu)))
+            .add_systems(Update, toggle_pause.run_if(in_state(GameState::Playing)))
             .add_systems(OnEnter(GameState::Bootstraping), spawn_debug_text)
             .add_systems(
                 Update,
-                update_debug_text.run_if(in_state(GameState::Playing)),
+                (
+                    toggle_debug_text,
+                    update_debug_text,
+                    spawn_wave_banner,
+                    despawn_wave_banner,
+                )
+                    .run_if(in_state(GameState::Playing)),
             );
     }
 }
@@ -28,47 +60,103 @@ impl Plugin for GUIPlugin {
 #[derive(Component)]
 struct MenuItem;
 
+#[derive(Component)]
+struct GameOverItem;
+
+#[derive(Component)]
+struct PauseMenuItem;
+
+#[derive(Component)]
+struct SettingsMenuItem;
+
+#[derive(Component)]
+struct LevelUpMenuItem;
+
+/// Picks the upgrade at this index of the `UpgradeChoices`.
+#[derive(Component, Clone, Copy)]
+struct UpgradeButton(u8);
+
+#[derive(Component, Clone, Copy)]
+enum MenuAction {
+    Play,
+    NextMap,
+    MainMenu,
+    Resume,
+    Settings,
+    ToggleFullscreen,
+    Back,
+}
+
 fn spawn_menu(mut commands: Commands) {
     commands
         .spawn(NodeBundle {
             style: Style {
                 width: Val::Percent(100.0),
                 height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
                 align_items: AlignItems::Center,
                 justify_content: JustifyContent::Center,
+                row_gap: Val::Px(10.0),
                 ..default()
             },
             ..default()
         })
         .with_children(|parent| {
-            parent
-                .spawn(ButtonBundle {
-                    style: Style {
-                        width: Val::Px(150.0),
-                        height: Val::Px(65.0),
-                        border: UiRect::all(Val::Px(5.0)),
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
-                        ..default()
-                    },
-                    border_color: BorderColor(Color::BLACK),
-                    background_color: BackgroundColor(Color::WHITE),
-                    ..default()
-                })
-                .with_children(|parent| {
-                    parent.spawn(TextBundle::from_section(
-                        "Play",
-                        TextStyle {
-                            font_size: 40.0,
-                            color: Color::BLACK,
-                            ..default()
-                        },
-                    ));
-                });
+            spawn_button(parent, "Play", MenuAction::Play);
+            spawn_button(parent, "", MenuAction::NextMap);
         })
         .insert(MenuItem);
 }
 
+fn spawn_button(parent: &mut ChildBuilder, label: &str, action: impl Bundle) {
+    parent
+        .spawn(ButtonBundle {
+            style: Style {
+                min_width: Val::Px(250.0),
+                height: Val::Px(65.0),
+                border: UiRect::all(Val::Px(5.0)),
+                padding: UiRect::horizontal(Val::Px(15.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            border_color: BorderColor(Color::BLACK),
+            background_color: BackgroundColor(Color::WHITE),
+            ..default()
+        })
+        .insert(action)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::BLACK,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn update_map_label(
+    selected: Res<SelectedMap>,
+    button_query: Query<(&MenuAction, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    let name = selected.0.as_ref().map_or("Random", |map| &map.name);
+
+    for (action, children) in button_query.iter() {
+        if !matches!(action, MenuAction::NextMap) {
+            continue;
+        }
+
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = format!("Map: {name}");
+            }
+        }
+    }
+}
+
 fn despawn_menu(mut commands: Commands, menu_item_query: Query<Entity, With<MenuItem>>) {
     for e in menu_item_query.iter() {
         commands.entity(e).despawn_recursive();
@@ -76,35 +164,293 @@ fn despawn_menu(mut commands: Commands, menu_item_query: Query<Entity, With<Menu
 }
 
 fn handle_menu_input(
-    interaction_query: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    interaction_query: Query<(&Interaction, &MenuAction), (Changed<Interaction>, With<Button>)>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    maps: Res<MapList>,
+    definitions: Res<Assets<MapDefinition>>,
+    mut selected: ResMut<SelectedMap>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut next_play_state: ResMut<NextState<PlayState>>,
+) {
+    for (interaction, action) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match action {
+            MenuAction::Play => next_state.set(GameState::Bootstraping),
+            MenuAction::NextMap => *selected = maps.next(&selected, &definitions),
+            MenuAction::MainMenu => next_state.set(GameState::Menu),
+            MenuAction::Resume => next_play_state.set(PlayState::Running),
+            MenuAction::Settings => next_play_state.set(PlayState::Settings),
+            MenuAction::Back => next_play_state.set(PlayState::Paused),
+            MenuAction::ToggleFullscreen => {
+                if let Ok(mut window) = window_query.get_single_mut() {
+                    window.mode = match window.mode {
+                        WindowMode::Windowed => WindowMode::BorderlessFullscreen,
+                        _ => WindowMode::Windowed,
+                    };
+                }
+            }
+        }
+    }
+}
+
+fn toggle_pause(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    play_state: Res<State<PlayState>>,
+    mut next_play_state: ResMut<NextState<PlayState>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Escape) {
+        return;
+    }
+
+    next_play_state.set(match play_state.get() {
+        PlayState::Running => PlayState::Paused,
+        PlayState::Paused => PlayState::Running,
+        PlayState::Settings => PlayState::Paused,
+        // An upgrade has to be picked first
+        PlayState::LevelUp => return,
+    });
+}
+
+fn spawn_overlay(commands: &mut Commands, title: &str, buttons: &[(&str, MenuAction)]) -> Entity {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            z_index: ZIndex::Global(10),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                title,
+                TextStyle {
+                    font_size: 80.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+
+            for (label, action) in buttons {
+                spawn_button(parent, label, *action);
+            }
+        })
+        .id()
+}
+
+fn spawn_pause_menu(mut commands: Commands) {
+    let overlay = spawn_overlay(
+        &mut commands,
+        "Paused",
+        &[
+            ("Resume", MenuAction::Resume),
+            ("Settings", MenuAction::Settings),
+            ("Quit", MenuAction::MainMenu),
+        ],
+    );
+
+    commands.entity(overlay).insert(PauseMenuItem);
+}
+
+fn despawn_pause_menu(
+    mut commands: Commands,
+    pause_item_query: Query<Entity, With<PauseMenuItem>>,
+) {
+    for e in pause_item_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn spawn_settings_menu(mut commands: Commands) {
+    let overlay = spawn_overlay(
+        &mut commands,
+        "Settings",
+        &[
+            ("Fullscreen", MenuAction::ToggleFullscreen),
+            ("Back", MenuAction::Back),
+        ],
+    );
+
+    commands.entity(overlay).insert(SettingsMenuItem);
+}
+
+fn despawn_settings_menu(
+    mut commands: Commands,
+    settings_item_query: Query<Entity, With<SettingsMenuItem>>,
+) {
+    for e in settings_item_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn pause_for_level_up(
+    choices: Res<UpgradeChoices>,
+    selection: Res<UpgradeSelection>,
+    replay_mode: Res<ReplayMode>,
+    mut next_play_state: ResMut<NextState<PlayState>>,
 ) {
-    for interaction in interaction_query.iter() {
-        match interaction {
-            Interaction::Pressed => next_state.set(GameState::Bootstraping),
-            _ => {}
+    // Replays already contain the picked upgrade
+    if choices.0.is_empty() || selection.0.is_some() || matches!(*replay_mode, ReplayMode::Playback)
+    {
+        return;
+    }
+
+    next_play_state.set(PlayState::LevelUp);
+}
+
+fn spawn_level_up_menu(mut commands: Commands, choices: Res<UpgradeChoices>) {
+    let overlay = spawn_overlay(&mut commands, "Level up", &[]);
+
+    commands
+        .entity(overlay)
+        .insert(LevelUpMenuItem)
+        .with_children(|parent| {
+            for (index, upgrade) in choices.0.iter().enumerate() {
+                spawn_button(parent, &upgrade.label(), UpgradeButton(index as u8));
+            }
+        });
+}
+
+fn despawn_level_up_menu(
+    mut commands: Commands,
+    level_up_item_query: Query<Entity, With<LevelUpMenuItem>>,
+) {
+    for e in level_up_item_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn handle_upgrade_input(
+    interaction_query: Query<(&Interaction, &UpgradeButton), (Changed<Interaction>, With<Button>)>,
+    mut selection: ResMut<UpgradeSelection>,
+    mut next_play_state: ResMut<NextState<PlayState>>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction == Interaction::Pressed {
+            selection.0 = Some(button.0);
+            next_play_state.set(PlayState::Running);
         }
     }
 }
 
+fn spawn_game_over_screen(mut commands: Commands, stats: Res<RunStats>, rng: Res<GameRng>) {
+    let minutes = stats.time_survived as u32 / 60;
+    let seconds = stats.time_survived as u32 % 60;
+
+    let lines = [
+        format!("Time survived: {minutes}:{seconds:02}"),
+        format!("Kills: {}", stats.kills),
+        format!("Damage dealt: {:.0}", stats.damage_dealt),
+        format!("Accuracy: {:.0}%", stats.accuracy() * 100.0),
+        format!("Waves cleared: {}", stats.waves_cleared),
+        format!("Seed: {}", rng.seed()),
+    ];
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Game over",
+                TextStyle {
+                    font_size: 80.0,
+                    color: Color::BLACK,
+                    ..default()
+                },
+            ));
+
+            for line in lines {
+                parent.spawn(TextBundle::from_section(
+                    line,
+                    TextStyle {
+                        font_size: 35.0,
+                        color: Color::BLACK,
+                        ..default()
+                    },
+                ));
+            }
+
+            spawn_button(parent, "Retry", MenuAction::Play);
+            spawn_button(parent, "Main menu", MenuAction::MainMenu);
+        })
+        .insert(GameOverItem);
+}
+
+fn despawn_game_over_screen(
+    mut commands: Commands,
+    game_over_item_query: Query<Entity, With<GameOverItem>>,
+) {
+    for e in game_over_item_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
 #[derive(Component)]
 struct DebugText;
 
-fn spawn_debug_text(mut commands: Commands) {
+/// Whether the FPS / enemy count / health readout is shown, toggled with F1.
+#[derive(Resource, Default)]
+pub struct ShowDebugText(pub bool);
+
+fn spawn_debug_text(mut commands: Commands, font: Res<GlobalFont>) {
     commands.spawn((
         TextBundle::from_section(
-            "Hello\nworld",
+            "",
             TextStyle {
-                font_size: 50.0,
+                font: font.0.clone(),
+                font_size: 30.0,
                 color: Color::BLACK,
-                ..default()
             },
-        ),
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(15.0),
+            top: Val::Px(100.0),
+            ..default()
+        }),
         DebugText,
         GameEntity,
     ));
 }
 
+fn toggle_debug_text(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut show_debug_text: ResMut<ShowDebugText>,
+    mut debug_text_query: Query<&mut Visibility, With<DebugText>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F1) {
+        show_debug_text.0 = !show_debug_text.0;
+    }
+
+    for mut visibility in debug_text_query.iter_mut() {
+        *visibility = if show_debug_text.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
 fn update_debug_text(
     mut debug_text_query: Query<&mut Text, With<DebugText>>,
     diagnostics: Res<DiagnosticsStore>,
@@ -122,7 +468,76 @@ fn update_debug_text(
 
     if let Some(fps) = diagnostics.get(&FrameTimeDiagnosticsPlugin::FPS) {
         if let Some(value) = fps.smoothed() {
-            text.sections[0].value = format!("{value:.2}\n{num_enemies}\n{player_health}")
+            text.sections[0].value =
+                format!("FPS: {value:.2}\nEnemies: {num_enemies}\nHealth: {player_health}")
+        }
+    }
+}
+
+#[derive(Component)]
+struct WaveBanner(Timer);
+
+fn spawn_wave_banner(
+    mut commands: Commands,
+    mut wave_started_events: EventReader<WaveStarted>,
+    mut wave_cleared_events: EventReader<WaveCleared>,
+    banner_query: Query<Entity, With<WaveBanner>>,
+) {
+    let started = wave_started_events.read().last().map(|event| {
+        if event.boss {
+            format!("Boss wave {}", event.wave)
+        } else {
+            format!("Wave {}", event.wave)
+        }
+    });
+    let cleared = wave_cleared_events
+        .read()
+        .last()
+        .map(|event| format!("Wave {} cleared", event.wave));
+
+    let Some(message) = started.or(cleared) else {
+        return;
+    };
+
+    for e in banner_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                top: Val::Percent(20.0),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                message,
+                TextStyle {
+                    font_size: 60.0,
+                    color: Color::BLACK,
+                    ..default()
+                },
+            ));
+        })
+        .insert((
+            WaveBanner(Timer::from_seconds(2.0, TimerMode::Once)),
+            GameEntity,
+        ));
+}
+
+fn despawn_wave_banner(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut banner_query: Query<(&mut WaveBanner, Entity)>,
+) {
+    for (mut banner, e) in banner_query.iter_mut() {
+        if banner.0.tick(time.delta()).finished() {
+            commands.entity(e).despawn_recursive();
         }
     }
 }
@@ -0,0 +1,178 @@
+use std::time::Duration;
+
+use bevy::{
+    app::{AppExit, Plugins},
+    asset::AssetPlugin,
+    input::InputPlugin,
+    log::LogPlugin,
+    prelude::*,
+    state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+};
+
+use crate::{
+    constants::*,
+    enemy::Enemy,
+    experience::{Experience, UpgradeChoices},
+    input::PlayerInput,
+    player::{Health, Player},
+    replay::play_back_input,
+    rng::GameRng,
+    run_stats::RunStats,
+    state::{GameState, PlayState},
+    stats::{Stat, Stats},
+    wave::WaveDirector,
+    weapon::{ActiveWeapon, WeaponDefinition},
+    GameplayPlugins,
+};
+
+/// Builds an app that simulates the gameplay loop without a window or renderer.
+///
+/// Every `update` advances game time by exactly one fixed tick, and the run
+/// starts straight in `Bootstraping`, skipping asset loading and the menu.
+pub fn headless_app() -> App {
+    headless_app_with(GameplayPlugins)
+}
+
+/// Like `headless_app`, with a custom set of gameplay plugins.
+pub fn headless_app_with<M>(gameplay: impl Plugins<M>) -> App {
+    let mut app = App::new();
+
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        StatesPlugin,
+        InputPlugin,
+    ))
+    .insert_resource(Time::<Fixed>::from_hz(SIMULATION_HZ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        1.0 / SIMULATION_HZ,
+    )))
+    .insert_state(GameState::Bootstraping)
+    .add_sub_state::<PlayState>()
+    .add_plugins(gameplay);
+
+    app
+}
+
+/// Plays a headless run for a fixed number of ticks, then prints the results and exits.
+pub struct HeadlessPlugin {
+    pub ticks: u32,
+    /// Stand still, keep firing at the nearest enemy and take the first upgrade offered.
+    /// Turn off when input comes from a replay.
+    pub autopilot: bool,
+}
+
+#[derive(Resource)]
+struct HeadlessRun {
+    ticks: u32,
+    elapsed: u32,
+    autopilot: bool,
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        // `MinimalPlugins` leave warnings and load errors unreported
+        app.add_plugins(LogPlugin::default())
+            .insert_resource(HeadlessRun {
+                ticks: self.ticks,
+                elapsed: 0,
+                autopilot: self.autopilot,
+            })
+            .add_systems(
+                FixedPreUpdate,
+                aim_at_nearest_enemy
+                    .after(play_back_input)
+                    .run_if(in_state(PlayState::Running).and_then(autopilot_enabled)),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                count_ticks.run_if(in_state(PlayState::Running)),
+            )
+            .add_systems(OnExit(GameState::Playing), finish_run);
+    }
+}
+
+fn aim_at_nearest_enemy(
+    mut input: ResMut<PlayerInput>,
+    definitions: Res<Assets<WeaponDefinition>>,
+    player_query: Query<&Transform, With<Player>>,
+    weapon_query: Query<&Handle<WeaponDefinition>, With<ActiveWeapon>>,
+    enemy_query: Query<&Transform, With<Enemy>>,
+    choices: Res<UpgradeChoices>,
+) {
+    input.upgrade = (!choices.0.is_empty()).then_some(0);
+
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let player_position = player_transform.translation.truncate();
+    let range = weapon_query
+        .get_single()
+        .ok()
+        .and_then(|definition| definitions.get(definition))
+        .map_or(0.0, WeaponDefinition::range);
+
+    input.aim = enemy_query
+        .iter()
+        .map(|t| t.translation.truncate())
+        .min_by(|a, b| {
+            a.distance_squared(player_position)
+                .total_cmp(&b.distance_squared(player_position))
+        });
+
+    // Only hold the trigger once the target is reachable
+    input.fire = input
+        .aim
+        .is_some_and(|target| target.distance(player_position) <= range);
+}
+
+fn autopilot_enabled(run: Res<HeadlessRun>) -> bool {
+    run.autopilot
+}
+
+fn count_ticks(mut run: ResMut<HeadlessRun>, mut next_state: ResMut<NextState<GameState>>) {
+    run.elapsed += 1;
+
+    if run.elapsed >= run.ticks {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+// Runs before the game entities despawn so the final state can still be read
+#[allow(clippy::too_many_arguments)]
+fn finish_run(
+    run: Res<HeadlessRun>,
+    stats: Res<RunStats>,
+    rng: Res<GameRng>,
+    director: Res<WaveDirector>,
+    experience: Res<Experience>,
+    player_query: Query<(&Health, &Stats), With<Player>>,
+    enemy_query: Query<(), With<Enemy>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let (health, max_health) = player_query
+        .get_single()
+        .map_or((0.0, PLAYER_HEALTH), |(health, player_stats)| {
+            (health.0, player_stats.get(Stat::MaxHealth))
+        });
+
+    println!("Seed: {}", rng.seed());
+    println!("Ticks simulated: {} / {}", run.elapsed, run.ticks);
+    println!("Time survived: {:.1}s", stats.time_survived);
+    println!("Player health: {:.0} / {max_health:.0}", health.max(0.0));
+    println!("Level: {}", experience.level);
+    println!("Wave: {} ({} cleared)", director.wave, stats.waves_cleared);
+    println!("Enemies alive: {}", enemy_query.iter().count());
+    println!("Kills: {}", stats.kills);
+    println!("Damage dealt: {:.0}", stats.damage_dealt);
+    println!(
+        "Accuracy: {:.1}% ({} / {} projectiles)",
+        stats.accuracy() * 100.0,
+        stats.projectiles_hit,
+        stats.projectiles_fired
+    );
+
+    exit.send(AppExit::Success);
+}
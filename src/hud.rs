@@ -0,0 +1,310 @@
+use bevy::prelude::*;
+
+use crate::{
+    experience::Experience,
+    player::{Health, Player},
+    run_stats::RunStats,
+    state::GameState,
+    stats::{Stat, Stats},
+    wave::{WaveDirector, WavePhase},
+    weapon::{ActiveWeapon, Ammo, Inventory, WeaponDefinition},
+    world::GameEntity,
+    *,
+};
+
+pub struct HudPlugin;
+
+#[derive(Component)]
+struct HealthBarFill;
+
+#[derive(Component)]
+struct HealthText;
+
+#[derive(Component)]
+struct WaveText;
+
+#[derive(Component)]
+struct TimerText;
+
+#[derive(Component)]
+struct KillsText;
+
+#[derive(Component)]
+struct WeaponText;
+
+#[derive(Component)]
+struct XpBarFill;
+
+#[derive(Component)]
+struct LevelText;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Bootstraping), spawn_hud)
+            .add_systems(
+                Update,
+                (
+                    update_health_bar,
+                    update_wave_text,
+                    update_timer_text,
+                    update_kills_text,
+                    update_weapon_text,
+                    update_xp_bar,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn hud_text(font: &GlobalFont, value: &str, font_size: f32) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font: font.0.clone(),
+            font_size,
+            color: Color::BLACK,
+        },
+    )
+}
+
+fn spawn_hud(mut commands: Commands, font: Res<GlobalFont>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::SpaceBetween,
+                padding: UiRect::all(Val::Px(15.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            // Top row: health on the left, wave in the middle, timer and kills on the right
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        justify_content: JustifyContent::SpaceBetween,
+                        align_items: AlignItems::FlexStart,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                row_gap: Val::Px(5.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent
+                                .spawn(NodeBundle {
+                                    style: Style {
+                                        width: Val::Px(300.0),
+                                        height: Val::Px(25.0),
+                                        border: UiRect::all(Val::Px(3.0)),
+                                        ..default()
+                                    },
+                                    border_color: BorderColor(Color::BLACK),
+                                    background_color: BackgroundColor(Color::srgb(0.3, 0.1, 0.1)),
+                                    ..default()
+                                })
+                                .with_children(|parent| {
+                                    parent.spawn((
+                                        NodeBundle {
+                                            style: Style {
+                                                width: Val::Percent(100.0),
+                                                height: Val::Percent(100.0),
+                                                ..default()
+                                            },
+                                            background_color: BackgroundColor(Color::srgb(
+                                                0.8, 0.15, 0.15,
+                                            )),
+                                            ..default()
+                                        },
+                                        HealthBarFill,
+                                    ));
+                                });
+                            parent.spawn((hud_text(&font, "", 30.0), HealthText));
+                        });
+
+                    parent.spawn((hud_text(&font, "", 50.0), WaveText));
+
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::FlexEnd,
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((hud_text(&font, "", 50.0), TimerText));
+                            parent.spawn((hud_text(&font, "", 30.0), KillsText));
+                        });
+                });
+
+            // Bottom row: level and experience on the left, weapon and ammo on the right
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        justify_content: JustifyContent::SpaceBetween,
+                        align_items: AlignItems::FlexEnd,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                row_gap: Val::Px(5.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((hud_text(&font, "", 30.0), LevelText));
+                            parent
+                                .spawn(NodeBundle {
+                                    style: Style {
+                                        width: Val::Px(300.0),
+                                        height: Val::Px(15.0),
+                                        border: UiRect::all(Val::Px(3.0)),
+                                        ..default()
+                                    },
+                                    border_color: BorderColor(Color::BLACK),
+                                    background_color: BackgroundColor(Color::srgb(0.1, 0.2, 0.3)),
+                                    ..default()
+                                })
+                                .with_children(|parent| {
+                                    parent.spawn((
+                                        NodeBundle {
+                                            style: Style {
+                                                width: Val::Percent(0.0),
+                                                height: Val::Percent(100.0),
+                                                ..default()
+                                            },
+                                            background_color: BackgroundColor(Color::srgb_u8(
+                                                XP_GEM_COLOR.0,
+                                                XP_GEM_COLOR.1,
+                                                XP_GEM_COLOR.2,
+                                            )),
+                                            ..default()
+                                        },
+                                        XpBarFill,
+                                    ));
+                                });
+                        });
+
+                    parent.spawn((hud_text(&font, "", 40.0), WeaponText));
+                });
+        })
+        .insert(GameEntity);
+}
+
+fn update_health_bar(
+    player_query: Query<(&Health, &Stats), With<Player>>,
+    mut fill_query: Query<&mut Style, With<HealthBarFill>>,
+    mut text_query: Query<&mut Text, With<HealthText>>,
+) {
+    let (Ok((health, stats)), Ok(mut fill), Ok(mut text)) = (
+        player_query.get_single(),
+        fill_query.get_single_mut(),
+        text_query.get_single_mut(),
+    ) else {
+        return;
+    };
+
+    let health = health.0.max(0.0);
+    let max_health = stats.get(Stat::MaxHealth);
+
+    fill.width = Val::Percent(health / max_health * 100.0);
+    text.sections[0].value = format!("{health:.0} / {max_health:.0}");
+}
+
+fn update_xp_bar(
+    experience: Res<Experience>,
+    mut fill_query: Query<&mut Style, With<XpBarFill>>,
+    mut text_query: Query<&mut Text, With<LevelText>>,
+) {
+    let (Ok(mut fill), Ok(mut text)) = (fill_query.get_single_mut(), text_query.get_single_mut())
+    else {
+        return;
+    };
+
+    let progress = experience.xp as f32 / experience.xp_to_next_level() as f32;
+
+    fill.width = Val::Percent(progress * 100.0);
+    text.sections[0].value = format!("Level {}", experience.level);
+}
+
+fn update_wave_text(director: Res<WaveDirector>, mut text_query: Query<&mut Text, With<WaveText>>) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    text.sections[0].value = match (director.wave, director.phase) {
+        (0, _) => String::new(),
+        (wave, WavePhase::Resting) => format!("Wave {} incoming", wave + 1),
+        (wave, _) => format!("Wave {wave}"),
+    };
+}
+
+fn update_timer_text(stats: Res<RunStats>, mut text_query: Query<&mut Text, With<TimerText>>) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    let seconds = stats.time_survived as u32;
+
+    text.sections[0].value = format!("{:02}:{:02}", seconds / 60, seconds % 60);
+}
+
+fn update_kills_text(stats: Res<RunStats>, mut text_query: Query<&mut Text, With<KillsText>>) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    text.sections[0].value = format!("Kills: {}", stats.kills);
+}
+
+fn update_weapon_text(
+    definitions: Res<Assets<WeaponDefinition>>,
+    player_query: Query<&Inventory, With<Player>>,
+    weapon_query: Query<(&Ammo, &Handle<WeaponDefinition>), With<ActiveWeapon>>,
+    mut text_query: Query<&mut Text, With<WeaponText>>,
+) {
+    let (Ok(inventory), Ok((ammo, definition)), Ok(mut text)) = (
+        player_query.get_single(),
+        weapon_query.get_single(),
+        text_query.get_single_mut(),
+    ) else {
+        return;
+    };
+
+    let Some(definition) = definitions.get(definition) else {
+        return;
+    };
+
+    let ammo_text = match (ammo.remaining(definition), definition.magazine_size) {
+        _ if ammo.reload.is_some() => "reloading".to_string(),
+        (Some(remaining), Some(size)) => format!("{remaining} / {size}"),
+        _ => "inf".to_string(),
+    };
+
+    text.sections[0].value = format!(
+        "{}/{} {}  {ammo_text}",
+        inventory.active + 1,
+        inventory.weapons.len(),
+        definition.name
+    );
+}
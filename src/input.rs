@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+
+use bevy::input::mouse::MouseWheel;
+
+use crate::{
+    experience::UpgradeSelection,
+    player::Player,
+    state::{GameState, PlayState},
+    weapon::{Inventory, WeaponSelection},
+    CursorPosition,
+};
+
+pub struct PlayerInputPlugin;
+
+/// Everything the player controls during a fixed tick.
+///
+/// Gameplay systems read this instead of the raw input devices, so a tick can be
+/// driven by the keyboard and mouse, a replay or a bot alike.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq)]
+pub struct PlayerInput {
+    /// Pressed direction keys, each axis is -1, 0 or 1.
+    pub movement: Vec2,
+    /// World position the weapon points at.
+    pub aim: Option<Vec2>,
+    pub fire: bool,
+    pub reload: bool,
+    /// Index into the offered `UpgradeChoices`.
+    pub upgrade: Option<u8>,
+    /// Inventory slot to switch to.
+    pub weapon: Option<u8>,
+}
+
+/// Samples `PlayerInput` from the input devices at the start of every fixed tick.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlayerInputSet;
+
+const WEAPON_SLOT_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+impl Plugin for PlayerInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerInput>()
+            .add_systems(Update, select_weapon.run_if(in_state(PlayState::Running)))
+            .add_systems(
+                FixedPreUpdate,
+                sample_player_input
+                    .in_set(PlayerInputSet)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn sample_player_input(
+    mut input: ResMut<PlayerInput>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    cursor_position: Res<CursorPosition>,
+    mut upgrade_selection: ResMut<UpgradeSelection>,
+    mut weapon_selection: ResMut<WeaponSelection>,
+) {
+    let pressed = |keys: [KeyCode; 2]| {
+        if keyboard_input.any_pressed(keys) {
+            1.0
+        } else {
+            0.0
+        }
+    };
+
+    *input = PlayerInput {
+        movement: Vec2::new(
+            pressed([KeyCode::KeyD, KeyCode::ArrowRight])
+                - pressed([KeyCode::KeyA, KeyCode::ArrowLeft]),
+            pressed([KeyCode::KeyW, KeyCode::ArrowUp])
+                - pressed([KeyCode::KeyS, KeyCode::ArrowDown]),
+        ),
+        aim: cursor_position.0,
+        fire: mouse_button_input.pressed(MouseButton::Left),
+        reload: keyboard_input.pressed(KeyCode::KeyR),
+        upgrade: upgrade_selection.0.take(),
+        weapon: weapon_selection.0.take(),
+    };
+}
+
+/// Picks an inventory slot every frame, key presses and wheel scrolls can be shorter than a tick.
+fn select_weapon(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut wheel_events: EventReader<MouseWheel>,
+    player_query: Query<&Inventory, With<Player>>,
+    mut selection: ResMut<WeaponSelection>,
+) {
+    let scroll = wheel_events.read().map(|event| event.y).sum::<f32>();
+
+    let Ok(inventory) = player_query.get_single() else {
+        return;
+    };
+
+    let slots = inventory.weapons.len();
+
+    if slots == 0 {
+        return;
+    }
+
+    let current = selection.0.map_or(inventory.active, |slot| slot as usize);
+
+    let slot = if let Some(slot) = WEAPON_SLOT_KEYS
+        .iter()
+        .position(|key| keyboard_input.just_pressed(*key))
+    {
+        slot
+    } else if scroll > 0.0 {
+        (current + slots - 1) % slots
+    } else if scroll < 0.0 {
+        (current + 1) % slots
+    } else {
+        return;
+    };
+
+    if slot < slots {
+        selection.0 = Some(slot as u8);
+    }
+}
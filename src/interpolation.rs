@@ -0,0 +1,70 @@
+use bevy::{app::RunFixedMainLoop, prelude::*, time::run_fixed_main_schedule};
+
+pub struct InterpolationPlugin;
+
+/// Smooths the rendered position of an entity that is moved in `FixedUpdate`.
+///
+/// Gameplay systems keep reading and writing `Transform` as usual, the simulated
+/// translation is restored before the fixed loop runs and blended again afterwards.
+#[derive(Component)]
+pub struct InterpolatedTranslation {
+    previous: Vec3,
+    current: Vec3,
+}
+
+impl InterpolatedTranslation {
+    pub fn new(translation: Vec3) -> Self {
+        Self {
+            previous: translation,
+            current: translation,
+        }
+    }
+
+    /// Latest simulated translation, `Transform` lags behind it outside the fixed loop.
+    pub fn current(&self) -> Vec3 {
+        self.current
+    }
+
+    /// Moves to `translation` without blending from the old position.
+    pub fn snap(&mut self, translation: Vec3) {
+        self.previous = translation;
+        self.current = translation;
+    }
+}
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            RunFixedMainLoop,
+            (
+                restore_simulated_translation.before(run_fixed_main_schedule),
+                interpolate_translation.after(run_fixed_main_schedule),
+            ),
+        )
+        .add_systems(FixedFirst, store_previous_translation);
+    }
+}
+
+fn restore_simulated_translation(mut query: Query<(&mut Transform, &InterpolatedTranslation)>) {
+    for (mut transform, interpolated) in query.iter_mut() {
+        transform.translation = interpolated.current;
+    }
+}
+
+fn store_previous_translation(mut query: Query<(&Transform, &mut InterpolatedTranslation)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        interpolated.previous = transform.translation;
+    }
+}
+
+fn interpolate_translation(
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Transform, &mut InterpolatedTranslation)>,
+) {
+    let alpha = fixed_time.overstep_fraction();
+
+    for (mut transform, mut interpolated) in query.iter_mut() {
+        interpolated.current = transform.translation;
+        transform.translation = interpolated.previous.lerp(interpolated.current, alpha);
+    }
+}
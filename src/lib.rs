@@ -1,14 +1,67 @@
+// Bevy queries and system params routinely trip this lint
+#![allow(clippy::type_complexity)]
+
 pub mod animation;
 pub mod camera;
 pub mod collision;
 pub mod constants;
+pub mod debug;
 pub mod enemy;
+pub mod experience;
 pub mod gui;
+pub mod headless;
+pub mod hud;
+pub mod input;
+pub mod interpolation;
+pub mod map;
+pub mod navigation;
+pub mod pickup;
 pub mod player;
+pub mod replay;
 pub mod resources;
+pub mod rng;
+pub mod run_stats;
+pub mod schedule;
+pub mod spatial;
 pub mod state;
+pub mod stats;
+pub mod steering;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod tilemap;
+pub mod wave;
 pub mod weapon;
 pub mod world;
 
 pub use constants::*;
 pub use resources::*;
+
+use bevy::{app::PluginGroupBuilder, prelude::*};
+
+/// Plugins simulating a run, shared by the windowed game, headless mode and tests.
+pub struct GameplayPlugins;
+
+impl PluginGroup for GameplayPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(ResourcesPlugin)
+            .add(schedule::SchedulePlugin)
+            .add(rng::RngPlugin)
+            .add(input::PlayerInputPlugin)
+            .add(replay::ReplayPlugin)
+            .add(interpolation::InterpolationPlugin)
+            .add(world::WorldPlugin)
+            .add(map::MapPlugin)
+            .add(tilemap::TileMapPlugin)
+            .add(collision::CollisionPlugin)
+            .add(navigation::NavigationPlugin)
+            .add(player::PlayerPlugin)
+            .add(weapon::WeaponPlugin)
+            .add(enemy::EnemyPlugin)
+            .add(wave::WavePlugin)
+            .add(experience::ExperiencePlugin)
+            .add(pickup::PickupPlugin)
+            .add(stats::StatsPlugin)
+            .add(run_stats::RunStatsPlugin)
+    }
+}
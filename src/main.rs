@@ -1,18 +1,69 @@
-use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
+use std::path::PathBuf;
+
+use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
 use bevy::prelude::*;
 
 use shooter2d::animation::AnimationPlugin;
 use shooter2d::camera::FollowCameraPlugin;
-use shooter2d::collision::CollisionPlugin;
-use shooter2d::enemy::EnemyPlugin;
+use shooter2d::debug::DebugOverlayPlugin;
 use shooter2d::gui::GUIPlugin;
-use shooter2d::player::PlayerPlugin;
-use shooter2d::state::GameState;
-use shooter2d::weapon::WeaponPlugin;
-use shooter2d::world::WorldPlugin;
-use shooter2d::{constants::*, ResourcesPlugin};
+use shooter2d::headless::{headless_app, HeadlessPlugin};
+use shooter2d::hud::HudPlugin;
+use shooter2d::map::{MapDefinition, SelectedMap};
+use shooter2d::replay::{Replay, ReplayMode};
+use shooter2d::rng::RngSeed;
+use shooter2d::state::{GameState, PlayState};
+use shooter2d::{constants::*, GameplayPlugins};
+
+fn main() -> AppExit {
+    let args = std::env::args().collect::<Vec<_>>();
+
+    let replay = flag_value(&args, "--replay").map(|path| {
+        Replay::load(path).unwrap_or_else(|err| panic!("could not load replay {path}: {err}"))
+    });
+    let seed = match &replay {
+        Some(replay) => Some(replay.seed),
+        None => flag_value(&args, "--seed")
+            .map(|seed| seed.parse::<u64>().expect("--seed expects a number")),
+    };
+    let record = flag_value(&args, "--record").map(PathBuf::from);
+    let map = match &replay {
+        Some(replay) => replay.map.clone(),
+        None => flag_value(&args, "--map").map(|path| {
+            MapDefinition::load(path)
+                .unwrap_or_else(|err| panic!("could not load map {path}: {err}"))
+        }),
+    };
+
+    if let Some(position) = args.iter().position(|arg| arg == "--headless") {
+        let ticks = match args.get(position + 1).filter(|arg| !arg.starts_with("--")) {
+            Some(ticks) => ticks.parse().expect("--headless expects a number of ticks"),
+            None => HEADLESS_DEFAULT_TICKS,
+        };
+        let replay_mode = match (&replay, record) {
+            (Some(_), _) => ReplayMode::Playback,
+            (None, Some(path)) => ReplayMode::Record(path),
+            (None, None) => ReplayMode::Off,
+        };
+
+        return headless_app()
+            .insert_resource(RngSeed(seed))
+            .insert_resource(SelectedMap(map))
+            .insert_resource(replay_mode)
+            .add_plugins(HeadlessPlugin {
+                ticks,
+                autopilot: replay.is_none(),
+            })
+            .insert_resource(replay.unwrap_or_default())
+            .run();
+    }
+
+    // Every run is recorded so playtesters can attach it to bug reports
+    let replay_mode = match replay {
+        Some(_) => ReplayMode::Playback,
+        None => ReplayMode::Record(record.unwrap_or_else(last_replay_path)),
+    };
 
-fn main() {
     App::new()
         .add_plugins(
             DefaultPlugins
@@ -33,22 +84,41 @@ fn main() {
             WINDOW_BG_COLOR.2,
         )))
         .insert_resource(Msaa::Off)
+        .insert_resource(Time::<Fixed>::from_hz(SIMULATION_HZ))
+        .insert_resource(RngSeed(seed))
+        .insert_resource(SelectedMap(map))
+        .insert_resource(replay_mode)
+        .insert_resource(replay.unwrap_or_default())
         // Development Plugins
-        .add_plugins(LogDiagnosticsPlugin::default())
         .add_plugins(FrameTimeDiagnosticsPlugin)
+        .add_plugins(DebugOverlayPlugin)
         // Game state
         .init_state::<GameState>()
+        .add_sub_state::<PlayState>()
+        // Presentation plugins
+        .add_plugins((GUIPlugin, HudPlugin, AnimationPlugin, FollowCameraPlugin))
         // Game plugins
-        .add_plugins((
-            ResourcesPlugin,
-            GUIPlugin,
-            AnimationPlugin,
-            FollowCameraPlugin,
-            WorldPlugin,
-            CollisionPlugin,
-            PlayerPlugin,
-            WeaponPlugin,
-            EnemyPlugin,
-        ))
-        .run();
+        .add_plugins(GameplayPlugins)
+        .run()
+}
+
+/// Where windowed runs are recorded without `--record`, in the user's cache directory.
+fn last_replay_path() -> PathBuf {
+    let var = |name| std::env::var_os(name).map(PathBuf::from);
+    let cache = var("XDG_CACHE_HOME")
+        .or_else(|| var("LOCALAPPDATA"))
+        .or_else(|| var("HOME").map(|home| home.join(".cache")))
+        .unwrap_or_else(std::env::temp_dir);
+
+    cache.join(CACHE_DIRECTORY).join(LAST_REPLAY_FILE)
+}
+
+/// Value following `flag` on the command line.
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let position = args.iter().position(|arg| arg == flag)?;
+
+    Some(
+        args.get(position + 1)
+            .unwrap_or_else(|| panic!("{flag} expects a value")),
+    )
 }
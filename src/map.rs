@@ -0,0 +1,242 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use bevy::{
+    asset::{io::Reader, ron, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{constants::*, state::GameState};
+
+pub struct MapPlugin;
+
+/// Designer-authored arena, loaded from `assets/maps/*.map.ron`.
+///
+/// Layers are drawn as text, one string per row from the top, one character per tile.
+/// Every character maps to an atlas index through the `legend`, `.` and spaces are empty
+/// tiles. Tile coordinates are `(column, row)` counted from the top left, like the text.
+#[derive(Asset, TypePath, Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MapDefinition {
+    pub name: String,
+    pub legend: BTreeMap<char, usize>,
+    #[serde(default)]
+    pub ground: Vec<String>,
+    #[serde(default)]
+    pub decorations: Vec<String>,
+    /// Tiles blocking the player, enemies and projectiles.
+    #[serde(default)]
+    pub solid: Vec<String>,
+    pub player_spawn: (u32, u32),
+    /// Areas enemies spawn in, anywhere around the player when empty.
+    #[serde(default)]
+    pub enemy_spawn_zones: Vec<SpawnZone>,
+    #[serde(default)]
+    pub pickups: Vec<(u32, u32)>,
+}
+
+/// Rectangle of tiles, both corners included.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct SpawnZone {
+    pub min: (u32, u32),
+    pub max: (u32, u32),
+}
+
+#[derive(Debug, Error)]
+pub enum MapError {
+    #[error("could not read map: {0}")]
+    Io(#[from] io::Error),
+    #[error("could not parse map: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("map has no tiles")]
+    Empty,
+    #[error("{layer} layer is not {width}x{height} tiles")]
+    LayerSize {
+        layer: &'static str,
+        width: u32,
+        height: u32,
+    },
+    #[error("tile '{0}' is missing from the legend")]
+    UnknownTile(char),
+    #[error("{0} is outside the map")]
+    OutOfBounds(&'static str),
+    #[error("player spawn is on a solid tile")]
+    SpawnBlocked,
+}
+
+impl MapDefinition {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MapError> {
+        Self::parse(&fs::read(path)?)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, MapError> {
+        let map: Self = ron::de::from_bytes(bytes)?;
+        map.validate()?;
+
+        Ok(map)
+    }
+
+    /// Size in tiles, taken from the first layer that isn't empty.
+    pub fn size(&self) -> UVec2 {
+        self.layers()
+            .into_iter()
+            .map(|(_, rows)| rows)
+            .find(|rows| !rows.is_empty())
+            .map_or(UVec2::ZERO, |rows| {
+                UVec2::new(rows[0].chars().count() as u32, rows.len() as u32)
+            })
+    }
+
+    fn layers(&self) -> [(&'static str, &[String]); 3] {
+        [
+            ("ground", &self.ground),
+            ("decorations", &self.decorations),
+            ("solid", &self.solid),
+        ]
+    }
+
+    /// Atlas index of every tile in a layer, rows from the top.
+    pub fn tiles<'a>(&'a self, rows: &'a [String]) -> impl Iterator<Item = (UVec2, usize)> + 'a {
+        rows.iter().enumerate().flat_map(move |(y, row)| {
+            row.chars().enumerate().filter_map(move |(x, tile)| {
+                let sprite = *self.legend.get(&tile)?;
+
+                Some((UVec2::new(x as u32, y as u32), sprite))
+            })
+        })
+    }
+
+    fn validate(&self) -> Result<(), MapError> {
+        let size = self.size();
+
+        if size.cmpeq(UVec2::ZERO).any() {
+            return Err(MapError::Empty);
+        }
+
+        for (layer, rows) in self.layers() {
+            let width_matches = rows.iter().all(|row| row.chars().count() as u32 == size.x);
+
+            if !rows.is_empty() && (rows.len() as u32 != size.y || !width_matches) {
+                return Err(MapError::LayerSize {
+                    layer,
+                    width: size.x,
+                    height: size.y,
+                });
+            }
+
+            let unknown = rows
+                .iter()
+                .flat_map(|row| row.chars())
+                .find(|tile| !is_empty_tile(*tile) && !self.legend.contains_key(tile));
+
+            if let Some(tile) = unknown {
+                return Err(MapError::UnknownTile(tile));
+            }
+        }
+
+        let inside = |(x, y): (u32, u32)| x < size.x && y < size.y;
+
+        if !inside(self.player_spawn) {
+            return Err(MapError::OutOfBounds("player spawn"));
+        }
+
+        if self
+            .enemy_spawn_zones
+            .iter()
+            .any(|zone| !inside(zone.min) || !inside(zone.max))
+        {
+            return Err(MapError::OutOfBounds("enemy spawn zone"));
+        }
+
+        if !self.pickups.iter().all(|pickup| inside(*pickup)) {
+            return Err(MapError::OutOfBounds("pickup"));
+        }
+
+        let (x, y) = self.player_spawn;
+
+        if self
+            .tiles(&self.solid)
+            .any(|(tile, _)| tile == UVec2::new(x, y))
+        {
+            return Err(MapError::SpawnBlocked);
+        }
+
+        Ok(())
+    }
+}
+
+fn is_empty_tile(tile: char) -> bool {
+    tile == '.' || tile == ' '
+}
+
+#[derive(Default)]
+struct MapDefinitionLoader;
+
+impl AssetLoader for MapDefinitionLoader {
+    type Asset = MapDefinition;
+    type Settings = ();
+    type Error = MapError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        MapDefinition::parse(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["map.ron"]
+    }
+}
+
+/// Map the next run is played on, a procedurally generated one when `None`.
+#[derive(Resource, Default, Clone)]
+pub struct SelectedMap(pub Option<MapDefinition>);
+
+/// Hand-authored maps offered in the menu.
+#[derive(Resource, Default)]
+pub struct MapList(pub Vec<Handle<MapDefinition>>);
+
+impl MapList {
+    /// Map following `current` in the list, wrapping back to a generated map after the last one.
+    pub fn next(&self, current: &SelectedMap, definitions: &Assets<MapDefinition>) -> SelectedMap {
+        let loaded = self
+            .0
+            .iter()
+            .filter_map(|handle| definitions.get(handle))
+            .collect::<Vec<_>>();
+
+        let position = current
+            .0
+            .as_ref()
+            .and_then(|map| loaded.iter().position(|loaded| loaded.name == map.name));
+        let next = match position {
+            Some(index) => loaded.get(index + 1),
+            None => loaded.first(),
+        };
+
+        SelectedMap(next.map(|map| (*map).clone()))
+    }
+}
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<MapDefinition>()
+            .init_asset_loader::<MapDefinitionLoader>()
+            .init_resource::<SelectedMap>()
+            .init_resource::<MapList>()
+            .add_systems(OnEnter(GameState::Loading), load_maps);
+    }
+}
+
+fn load_maps(mut maps: ResMut<MapList>, asset_server: Res<AssetServer>) {
+    maps.0 = MAP_PATHS
+        .iter()
+        .map(|path| asset_server.load(*path))
+        .collect();
+}
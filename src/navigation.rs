@@ -0,0 +1,245 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use bevy::prelude::*;
+
+use crate::{constants::*, player::Player, state::GameState, tilemap::TileMap};
+
+pub struct NavigationPlugin;
+
+/// Updates the `FlowField`, enemies steer along it afterwards.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NavigationSet;
+
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+const UNREACHABLE: u32 = u32::MAX;
+
+/// Distance to the player from every tile of the `TileMap`, enemies walk downhill to reach it.
+///
+/// The field is repaired whenever the player enters another tile. The previous distances plus
+/// the path cost between the old and new target are an upper bound of the new distances, so
+/// only tiles that got closer are searched again. The search is spread over several ticks, a
+/// limited number of tiles per tick so large maps never stall a frame, and enemies keep
+/// following the previous field until it is complete. A target set meanwhile is searched next.
+#[derive(Resource, Default)]
+pub struct FlowField {
+    size: UVec2,
+    origin: Vec2,
+    walkable: Vec<bool>,
+    distance: Vec<u32>,
+    target: Option<UVec2>,
+    search: Option<Search>,
+    queued: Option<UVec2>,
+}
+
+/// Dijkstra search towards a new target, spread over several ticks.
+struct Search {
+    target: UVec2,
+    distance: Vec<u32>,
+    open: BinaryHeap<Reverse<(u32, usize)>>,
+}
+
+impl FlowField {
+    pub fn new(map: &TileMap) -> Self {
+        Self {
+            size: UVec2::new(map.width, map.height),
+            origin: map.bounds().min,
+            walkable: map.solid.iter().map(Option::is_none).collect(),
+            ..default()
+        }
+    }
+
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    /// Tile the current field leads to, `None` until the first search finished.
+    pub fn target(&self) -> Option<UVec2> {
+        self.target
+    }
+
+    pub fn tile_at(&self, position: Vec2) -> Option<UVec2> {
+        let tile = ((position - self.origin) / WORLD_TILE_SIZE).floor();
+
+        (tile.cmpge(Vec2::ZERO).all() && tile.cmplt(self.size.as_vec2()).all())
+            .then(|| tile.as_uvec2())
+    }
+
+    pub fn tile_center(&self, tile: UVec2) -> Vec2 {
+        self.origin + (tile.as_vec2() + 0.5) * WORLD_TILE_SIZE
+    }
+
+    /// Path cost from `tile` to the target, `None` when it can't be reached.
+    pub fn distance(&self, tile: UVec2) -> Option<u32> {
+        self.distance
+            .get(self.index(tile))
+            .copied()
+            .filter(|distance| *distance != UNREACHABLE)
+    }
+
+    /// Starts searching towards `tile`, unless the field already leads there.
+    ///
+    /// A search in progress is finished first, `tile` is searched right after it.
+    pub fn set_target(&mut self, tile: UVec2) {
+        if let Some(search) = &self.search {
+            self.queued = (search.target != tile).then_some(tile);
+            return;
+        }
+
+        if self.target == Some(tile) {
+            return;
+        }
+
+        // Every path to the old target extends to the new one, only shorter paths are searched
+        let mut distance: Vec<u32> = match self.distance(tile) {
+            Some(offset) => self
+                .distance
+                .iter()
+                .map(|distance| distance.saturating_add(offset))
+                .collect(),
+            None => vec![UNREACHABLE; self.walkable.len()],
+        };
+        let mut open = BinaryHeap::new();
+        let index = self.index(tile);
+
+        distance[index] = 0;
+        open.push(Reverse((0, index)));
+
+        self.search = Some(Search {
+            target: tile,
+            distance,
+            open,
+        });
+    }
+
+    /// Expands up to `budget` tiles of the pending searches, returns whether the field is up to
+    /// date.
+    pub fn step(&mut self, budget: usize) -> bool {
+        for _ in 0..budget {
+            let Some(search) = &mut self.search else {
+                return true;
+            };
+
+            let Some(Reverse((distance, index))) = search.open.pop() else {
+                self.distance = std::mem::take(&mut search.distance);
+                self.target = Some(search.target);
+                self.search = None;
+
+                if let Some(tile) = self.queued.take() {
+                    self.set_target(tile);
+                }
+
+                continue;
+            };
+
+            // Already reached through a shorter path
+            if distance > search.distance[index] {
+                continue;
+            }
+
+            let tile = UVec2::new(index as u32 % self.size.x, index as u32 / self.size.x);
+
+            for (neighbour, cost) in neighbours(&self.walkable, self.size, tile) {
+                let neighbour = (neighbour.y * self.size.x + neighbour.x) as usize;
+                let distance = distance + cost;
+
+                if distance < search.distance[neighbour] {
+                    search.distance[neighbour] = distance;
+                    search.open.push(Reverse((distance, neighbour)));
+                }
+            }
+        }
+
+        self.search.is_none()
+    }
+
+    /// Heading towards the next tile on the way to the target.
+    ///
+    /// `None` when already in the target tile or when there is no path, enemies head straight
+    /// for the player then.
+    pub fn direction(&self, position: Vec2) -> Option<Vec2> {
+        let tile = self.tile_at(position)?;
+        let distance = self.distance(tile).filter(|distance| *distance > 0)?;
+
+        let (next, _) = neighbours(&self.walkable, self.size, tile)
+            .filter_map(|(neighbour, _)| Some((neighbour, self.distance(neighbour)?)))
+            .min_by_key(|(_, distance)| *distance)
+            .filter(|(_, next_distance)| *next_distance < distance)?;
+
+        (self.tile_center(next) - position).try_normalize()
+    }
+
+    fn index(&self, tile: UVec2) -> usize {
+        (tile.y * self.size.x + tile.x) as usize
+    }
+}
+
+/// Walkable tiles around `tile`, diagonals only when they don't cut a solid corner.
+fn neighbours(
+    walkable: &[bool],
+    size: UVec2,
+    tile: UVec2,
+) -> impl Iterator<Item = (UVec2, u32)> + '_ {
+    let is_walkable = move |tile: IVec2| {
+        tile.cmpge(IVec2::ZERO).all()
+            && tile.cmplt(size.as_ivec2()).all()
+            && walkable[(tile.y as u32 * size.x + tile.x as u32) as usize]
+    };
+    let tile = tile.as_ivec2();
+
+    [-1, 0, 1]
+        .into_iter()
+        .flat_map(|y| [-1, 0, 1].into_iter().map(move |x| IVec2::new(x, y)))
+        .filter(|step| *step != IVec2::ZERO)
+        .filter(move |step| {
+            is_walkable(tile + *step)
+                && is_walkable(tile + IVec2::new(step.x, 0))
+                && is_walkable(tile + IVec2::new(0, step.y))
+        })
+        .map(move |step| {
+            let cost = if step.x != 0 && step.y != 0 {
+                DIAGONAL_COST
+            } else {
+                STRAIGHT_COST
+            };
+
+            ((tile + step).as_uvec2(), cost)
+        })
+}
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlowField>().add_systems(
+            FixedUpdate,
+            (
+                rebuild_flow_field.run_if(resource_changed::<TileMap>),
+                update_flow_field,
+            )
+                .chain()
+                .in_set(NavigationSet)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn rebuild_flow_field(map: Res<TileMap>, mut field: ResMut<FlowField>) {
+    *field = FlowField::new(&map);
+}
+
+fn update_flow_field(mut field: ResMut<FlowField>, player_query: Query<&Transform, With<Player>>) {
+    let Ok(transform) = player_query.get_single() else {
+        return;
+    };
+
+    if let Some(tile) = field.tile_at(transform.translation.truncate()) {
+        field.set_target(tile);
+    }
+
+    // Nothing to follow yet, finish the first search right away
+    let budget = match field.target() {
+        Some(_) => NAVIGATION_TILES_PER_TICK,
+        None => usize::MAX,
+    };
+
+    field.step(budget);
+}
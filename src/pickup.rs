@@ -0,0 +1,282 @@
+use bevy::{prelude::*, utils::HashMap};
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
+
+use crate::{
+    collision::{Collider, EnemySpatialIndex},
+    enemy::{Enemy, EnemyKilledEvent, EnemyProjectile, EnemyType},
+    experience::{choose_upgrade, Magnetized, XpGem},
+    interpolation::InterpolatedTranslation,
+    player::{Health, Player},
+    rng::{reset_game_rng, GameRng, RngStream},
+    state::GameState,
+    stats::{Modifier, ModifierSource, Stat, Stats},
+    tilemap::{generate_tilemap, TileMap},
+    world::GameEntity,
+    *,
+};
+
+pub struct PickupPlugin;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PickupKind {
+    /// Restores some health, up to the maximum.
+    Heal,
+    /// Fires faster for a while.
+    FireRateBoost,
+    /// Damages every enemy and destroys every enemy projectile around the player.
+    Bomb,
+    /// Pulls in every XP gem on the map.
+    Magnet,
+}
+
+impl PickupKind {
+    pub const ALL: [PickupKind; 4] = [
+        PickupKind::Heal,
+        PickupKind::FireRateBoost,
+        PickupKind::Bomb,
+        PickupKind::Magnet,
+    ];
+
+    /// Gun and spare creature frames from the atlas, none of them a map tile, tinted with `color`.
+    fn sprite(&self) -> usize {
+        match self {
+            PickupKind::Heal => PICKUP_HEAL_SPRITE,
+            PickupKind::FireRateBoost => PICKUP_FIRE_RATE_BOOST_SPRITE,
+            PickupKind::Bomb => PICKUP_BOMB_SPRITE,
+            PickupKind::Magnet => PICKUP_MAGNET_SPRITE,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            PickupKind::Heal => Color::srgb_u8(80, 220, 90),
+            PickupKind::FireRateBoost => Color::srgb_u8(255, 170, 40),
+            PickupKind::Bomb => Color::srgb_u8(230, 40, 40),
+            PickupKind::Magnet => Color::srgb_u8(200, 80, 255),
+        }
+    }
+}
+
+/// Collectible lying in the world, applied once the player touches it.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Pickup(pub PickupKind);
+
+#[derive(Event, Clone)]
+pub struct PickupCollectedEvent {
+    pub pickup: Entity,
+    pub kind: PickupKind,
+}
+
+/// Chance of dropping a pickup and which one it is.
+#[derive(Debug, Clone, Default)]
+pub struct DropTable {
+    /// Chance of dropping anything at all, between 0 and 1.
+    pub chance: f32,
+    /// Relative weight of every pickup that can drop.
+    pub weights: Vec<(PickupKind, u32)>,
+}
+
+impl DropTable {
+    pub fn new(chance: f32, weights: &[(PickupKind, u32)]) -> Self {
+        Self {
+            chance,
+            weights: weights.to_vec(),
+        }
+    }
+
+    pub fn roll(&self, rng: &mut impl Rng) -> Option<PickupKind> {
+        if !rng.gen_bool(self.chance.clamp(0.0, 1.0) as f64) {
+            return None;
+        }
+
+        let table = WeightedIndex::new(self.weights.iter().map(|(_, weight)| *weight)).ok()?;
+
+        Some(self.weights[table.sample(rng)].0)
+    }
+}
+
+/// What dead enemies drop, and what lies on the pickup locations of a map.
+#[derive(Resource, Debug, Clone)]
+pub struct DropTables {
+    pub enemies: HashMap<EnemyType, DropTable>,
+    pub map: DropTable,
+}
+
+impl DropTables {
+    pub fn enemy(&self, kind: EnemyType) -> Option<&DropTable> {
+        self.enemies.get(&kind)
+    }
+}
+
+impl Default for DropTables {
+    fn default() -> Self {
+        use PickupKind::*;
+
+        let common = [(Heal, 5), (FireRateBoost, 3), (Magnet, 2), (Bomb, 1)];
+        let rare = [(Heal, 3), (FireRateBoost, 3), (Magnet, 3), (Bomb, 2)];
+
+        Self {
+            enemies: HashMap::from_iter([
+                (EnemyType::Grunt, DropTable::new(0.02, &common)),
+                (EnemyType::Runner, DropTable::new(0.02, &common)),
+                (EnemyType::Shooter, DropTable::new(0.05, &common)),
+                (EnemyType::Exploder, DropTable::new(0.05, &common)),
+                (EnemyType::Tank, DropTable::new(0.15, &rare)),
+                (
+                    EnemyType::Boss,
+                    DropTable::new(1.0, &[(Heal, 1), (Bomb, 1)]),
+                ),
+            ]),
+            map: DropTable::new(1.0, &[(Heal, 2), (FireRateBoost, 1), (Magnet, 1)]),
+        }
+    }
+}
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PickupCollectedEvent>()
+            .init_resource::<DropTables>()
+            .add_systems(
+                OnEnter(GameState::Bootstraping),
+                spawn_map_pickups
+                    .after(generate_tilemap)
+                    .after(reset_game_rng),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                (apply_pickups, drop_pickups)
+                    .chain()
+                    .after(choose_upgrade)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn spawn_map_pickups(
+    mut commands: Commands,
+    handle: Res<GlobalTextureAtlas>,
+    map: Res<TileMap>,
+    tables: Res<DropTables>,
+    mut rng: ResMut<GameRng>,
+) {
+    let rng = rng.stream(RngStream::Drops);
+
+    for location in &map.pickup_locations {
+        if let Some(kind) = tables.map.roll(rng) {
+            spawn_pickup(&mut commands, &handle, kind, *location);
+        }
+    }
+}
+
+fn drop_pickups(
+    mut commands: Commands,
+    handle: Res<GlobalTextureAtlas>,
+    tables: Res<DropTables>,
+    mut rng: ResMut<GameRng>,
+    mut events: EventReader<EnemyKilledEvent>,
+) {
+    let rng = rng.stream(RngStream::Drops);
+
+    for event in events.read() {
+        let Some(table) = tables.enemy(event.kind) else {
+            continue;
+        };
+
+        if let Some(kind) = table.roll(rng) {
+            spawn_pickup(&mut commands, &handle, kind, event.position);
+        }
+    }
+}
+
+pub fn spawn_pickup(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    kind: PickupKind,
+    position: Vec2,
+) -> Entity {
+    let translation = position.extend(0.8);
+
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: handle.image.clone().unwrap_or_default(),
+                sprite: Sprite {
+                    color: kind.color(),
+                    ..default()
+                },
+                transform: Transform::from_translation(translation)
+                    .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR * PICKUP_SCALE)),
+                ..default()
+            },
+            TextureAtlas {
+                layout: handle.layout.clone().unwrap_or_default(),
+                index: kind.sprite(),
+            },
+            Collider::sprite_circle(PICKUP_COLLIDER_RADIUS),
+            InterpolatedTranslation::new(translation),
+            Pickup(kind),
+            GameEntity,
+        ))
+        .id()
+}
+
+fn apply_pickups(
+    mut commands: Commands,
+    enemy_index: Res<EnemySpatialIndex>,
+    mut player_query: Query<(&Transform, &mut Health, &mut Stats), With<Player>>,
+    mut enemy_query: Query<&mut Enemy>,
+    enemy_projectile_query: Query<(&Transform, Entity), With<EnemyProjectile>>,
+    gem_query: Query<Entity, (With<XpGem>, Without<Magnetized>)>,
+    mut events: EventReader<PickupCollectedEvent>,
+) {
+    let Ok((transform, mut health, mut stats)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    let player_position = transform.translation.truncate();
+
+    for event in events.read() {
+        commands.entity(event.pickup).despawn();
+
+        match event.kind {
+            PickupKind::Heal => {
+                health.0 = (health.0 + PICKUP_HEAL_AMOUNT).min(stats.get(Stat::MaxHealth));
+            }
+            // Picking up another boost restarts it instead of stacking
+            PickupKind::FireRateBoost => stats.refresh_modifier(
+                Modifier::multiply(
+                    Stat::FireRate,
+                    PICKUP_FIRE_RATE_BOOST,
+                    ModifierSource::Pickup,
+                )
+                .lasting(PICKUP_FIRE_RATE_BOOST_DURATION),
+            ),
+            PickupKind::Bomb => {
+                for (e, _) in enemy_index
+                    .0
+                    .within_radius(player_position, PICKUP_BOMB_RADIUS)
+                {
+                    if let Ok(mut enemy) = enemy_query.get_mut(e) {
+                        enemy.health -= PICKUP_BOMB_DAMAGE;
+                    }
+                }
+
+                for (transform, entity) in enemy_projectile_query.iter() {
+                    let position = transform.translation.truncate();
+
+                    if position.distance(player_position) <= PICKUP_BOMB_RADIUS {
+                        commands.entity(entity).despawn();
+                    }
+                }
+            }
+            PickupKind::Magnet => {
+                for entity in gem_query.iter() {
+                    commands.entity(entity).insert(Magnetized);
+                }
+            }
+        }
+    }
+}
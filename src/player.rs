@@ -1,6 +1,13 @@
 use bevy::{math::vec3, prelude::*};
 
-use crate::{constants::*, state::GameState};
+use crate::{
+    constants::*,
+    enemy::handle_enemy_explosions,
+    input::PlayerInput,
+    schedule::GameplaySet,
+    state::GameState,
+    stats::{Stat, Stats},
+};
 
 pub struct PlayerPlugin;
 
@@ -10,6 +17,14 @@ pub struct Player;
 #[derive(Component)]
 pub struct Health(pub f32);
 
+/// Ignores incoming damage until the timer finishes.
+#[derive(Component)]
+pub struct Invulnerable(pub Timer);
+
+/// Velocity pushing the player away from the last hit, decays over time.
+#[derive(Component)]
+pub struct Knockback(pub Vec2);
+
 #[derive(Component, Default)]
 pub enum PlayerState {
     #[default]
@@ -19,56 +34,48 @@ pub enum PlayerState {
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<PlayerEnemyCollisionEvent>().add_systems(
-            Update,
-            (
-                handle_player_input,
-                handle_player_enemy_collision_events,
-                handle_player_death,
+        app.add_event::<PlayerEnemyCollisionEvent>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    (handle_player_input, apply_knockback)
+                        .chain()
+                        .in_set(GameplaySet::Movement),
+                    // Explosions land on the tick they go off
+                    (
+                        update_invulnerability,
+                        handle_player_enemy_collision_events,
+                        handle_player_death,
+                    )
+                        .chain()
+                        .in_set(GameplaySet::Damage)
+                        .after(handle_enemy_explosions),
+                )
+                    .run_if(in_state(GameState::Playing)),
             )
-                .run_if(in_state(GameState::Playing)),
-        );
+            .add_systems(
+                Update,
+                blink_invulnerable_player.run_if(in_state(GameState::Playing)),
+            );
     }
 }
 
 fn handle_player_input(
-    mut player_query: Query<(&mut Transform, &mut PlayerState), With<Player>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    input: Res<PlayerInput>,
+    mut player_query: Query<(&mut Transform, &mut PlayerState, &Stats), With<Player>>,
 ) {
     if player_query.is_empty() {
         return;
     }
 
-    let (mut transform, mut player_state) = player_query.single_mut();
-
-    let up_key: bool =
-        keyboard_input.pressed(KeyCode::KeyW) || keyboard_input.pressed(KeyCode::ArrowUp);
-    let right_key: bool =
-        keyboard_input.pressed(KeyCode::KeyD) || keyboard_input.pressed(KeyCode::ArrowRight);
-    let down_key: bool =
-        keyboard_input.pressed(KeyCode::KeyS) || keyboard_input.pressed(KeyCode::ArrowDown);
-    let left_key: bool =
-        keyboard_input.pressed(KeyCode::KeyA) || keyboard_input.pressed(KeyCode::ArrowLeft);
-
-    let mut delta = Vec2::ZERO;
-
-    if up_key {
-        delta.y += 1.0;
-    }
-    if down_key {
-        delta.y -= 1.0;
-    }
-    if left_key {
-        delta.x -= 1.0;
-    }
-    if right_key {
-        delta.x += 1.0;
-    }
+    let (mut transform, mut player_state, stats) = player_query.single_mut();
+    let speed = stats.get(Stat::Speed);
 
-    delta = delta.normalize();
+    let delta = input.movement.normalize_or_zero();
 
-    if delta.is_finite() && (up_key || right_key || down_key || left_key) {
-        transform.translation += vec3(delta.x, delta.y, 0.0) * PLAYER_SPEED;
+    if delta != Vec2::ZERO {
+        transform.translation += vec3(delta.x, delta.y, 0.0) * speed * time.delta_seconds();
         transform.translation.z = 10.0;
 
         *player_state = PlayerState::Moving;
@@ -77,25 +84,90 @@ fn handle_player_input(
     }
 }
 
-#[derive(Event)]
-pub struct PlayerEnemyCollisionEvent;
+#[derive(Event, Clone)]
+pub struct PlayerEnemyCollisionEvent {
+    /// Enemy or enemy projectile that hit the player.
+    pub attacker: Entity,
+    pub attacker_position: Vec2,
+    pub damage: f32,
+}
 
 fn handle_player_enemy_collision_events(
-    mut player_query: Query<&mut Health, With<Player>>,
+    mut commands: Commands,
+    mut player_query: Query<
+        (&Transform, &mut Health, Entity),
+        (With<Player>, Without<Invulnerable>),
+    >,
     mut events: EventReader<PlayerEnemyCollisionEvent>,
 ) {
+    // Only the strongest hit of a tick counts, the rest land inside the i-frame window.
+    // Harmless contacts (exploders) neither grant i-frames nor push the player.
+    let Some(hit) = events
+        .read()
+        .filter(|hit| hit.damage > 0.0)
+        .max_by(|a, b| a.damage.total_cmp(&b.damage))
+    else {
+        return;
+    };
+
     if player_query.is_empty() {
         return;
     }
 
-    let mut health = player_query.single_mut();
+    let (transform, mut health, entity) = player_query.single_mut();
 
-    for _ in events.read() {
-        health.0 -= ENEMY_DAMAGE
+    health.0 -= hit.damage;
+
+    let direction = (transform.translation.truncate() - hit.attacker_position).normalize_or_zero();
+
+    commands.entity(entity).insert((
+        Invulnerable(Timer::from_seconds(
+            PLAYER_INVULNERABILITY_DURATION,
+            TimerMode::Once,
+        )),
+        Knockback(direction * PLAYER_KNOCKBACK_IMPULSE),
+    ));
+}
+
+pub fn apply_knockback(
+    time: Res<Time>,
+    mut player_query: Query<(&mut Transform, &mut Knockback), With<Player>>,
+) {
+    for (mut transform, mut knockback) in player_query.iter_mut() {
+        transform.translation += knockback.0.extend(0.0) * time.delta_seconds();
+        knockback.0 *= (-PLAYER_KNOCKBACK_DECAY * time.delta_seconds()).exp();
+    }
+}
+
+fn update_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<(&mut Invulnerable, Entity), With<Player>>,
+) {
+    for (mut invulnerable, entity) in player_query.iter_mut() {
+        if invulnerable.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
+fn blink_invulnerable_player(
+    mut player_query: Query<(&mut Visibility, Option<&Invulnerable>), With<Player>>,
+) {
+    for (mut visibility, invulnerable) in player_query.iter_mut() {
+        let hidden = invulnerable.is_some_and(|invulnerable| {
+            invulnerable.0.elapsed_secs() / PLAYER_BLINK_INTERVAL % 2.0 < 1.0
+        });
+
+        *visibility = if hidden {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
     }
 }
 
-fn handle_player_death(
+pub fn handle_player_death(
     player_query: Query<&Health, With<Player>>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
@@ -106,6 +178,6 @@ fn handle_player_death(
     let health = player_query.single();
 
     if health.0 <= 0.0 {
-        next_state.set(GameState::Menu)
+        next_state.set(GameState::GameOver)
     }
 }
@@ -0,0 +1,313 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{asset::ron, prelude::*};
+use thiserror::Error;
+
+use crate::{
+    constants::*,
+    input::{PlayerInput, PlayerInputSet},
+    map::{MapDefinition, MapError, SelectedMap},
+    rng::{reset_game_rng, GameRng},
+    state::{GameState, PlayState},
+    tilemap::generate_tilemap,
+};
+
+pub struct ReplayPlugin;
+
+#[derive(Resource, Default)]
+pub enum ReplayMode {
+    #[default]
+    Off,
+    /// Records every run and saves it to the path once the run ends.
+    Record(PathBuf),
+    /// Feeds the `Replay` resource back instead of the input devices.
+    Playback,
+}
+
+/// Seed, map and per-tick input of a run, enough to reproduce it exactly.
+#[derive(Resource, Default, Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    /// Map the run was played on, a generated one when `None`.
+    pub map: Option<MapDefinition>,
+    pub inputs: Vec<PlayerInput>,
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("could not access replay file: {0}")]
+    Io(#[from] io::Error),
+    #[error("not a replay file")]
+    InvalidHeader,
+    #[error("unsupported replay version {0}")]
+    UnsupportedVersion(u8),
+    #[error("replay file is truncated")]
+    Truncated,
+    #[error("replay is longer than {REPLAY_MAX_TICKS} ticks")]
+    TooLong,
+    #[error("replay map is invalid: {0}")]
+    Map(#[from] MapError),
+}
+
+const MAGIC: &[u8; 4] = b"S2DR";
+const VERSION: u8 = 4;
+
+const UP: u16 = 1 << 0;
+const DOWN: u16 = 1 << 1;
+const LEFT: u16 = 1 << 2;
+const RIGHT: u16 = 1 << 3;
+const FIRE: u16 = 1 << 4;
+const RELOAD: u16 = 1 << 5;
+const AIM: u16 = 1 << 6;
+const UPGRADE: u16 = 1 << 7;
+const WEAPON: u16 = 1 << 8;
+
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        Self::decode(&fs::read(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ReplayError> {
+        if let Some(directory) = path.as_ref().parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        Ok(fs::write(path, self.encode())?)
+    }
+
+    /// Header and map followed by run-length encoded inputs, consecutive identical ticks share
+    /// one entry. The map is stored as RON text, its length is 0 for a generated map.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+
+        let map = self.map.as_ref().map_or_else(String::new, |map| {
+            ron::ser::to_string(map).expect("map definitions serialize to RON")
+        });
+
+        bytes.extend_from_slice(&(map.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(map.as_bytes());
+
+        for run in self.inputs.chunk_by(|a, b| a == b) {
+            let input = run[0];
+            let mut flags = 0;
+
+            flags |= if input.movement.y > 0.0 { UP } else { 0 };
+            flags |= if input.movement.y < 0.0 { DOWN } else { 0 };
+            flags |= if input.movement.x < 0.0 { LEFT } else { 0 };
+            flags |= if input.movement.x > 0.0 { RIGHT } else { 0 };
+            flags |= if input.fire { FIRE } else { 0 };
+            flags |= if input.reload { RELOAD } else { 0 };
+            flags |= if input.aim.is_some() { AIM } else { 0 };
+            flags |= if input.upgrade.is_some() { UPGRADE } else { 0 };
+            flags |= if input.weapon.is_some() { WEAPON } else { 0 };
+
+            bytes.extend_from_slice(&(run.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&flags.to_le_bytes());
+
+            if let Some(aim) = input.aim {
+                bytes.extend_from_slice(&aim.x.to_le_bytes());
+                bytes.extend_from_slice(&aim.y.to_le_bytes());
+            }
+
+            if let Some(upgrade) = input.upgrade {
+                bytes.push(upgrade);
+            }
+
+            if let Some(weapon) = input.weapon {
+                bytes.push(weapon);
+            }
+        }
+
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let mut reader = ByteReader(bytes);
+
+        if reader.take::<4>()? != *MAGIC {
+            return Err(ReplayError::InvalidHeader);
+        }
+
+        let [version] = reader.take::<1>()?;
+
+        if version != VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let seed = u64::from_le_bytes(reader.take()?);
+        let map = match u32::from_le_bytes(reader.take()?) {
+            0 => None,
+            length => Some(MapDefinition::parse(reader.take_slice(length as usize)?)?),
+        };
+        let mut inputs = Vec::new();
+
+        while !reader.0.is_empty() {
+            let count = u32::from_le_bytes(reader.take()?);
+            let flags = u16::from_le_bytes(reader.take()?);
+            let axis = |positive, negative| {
+                let pressed = |flag| if flags & flag != 0 { 1.0 } else { 0.0 };
+                pressed(positive) - pressed(negative)
+            };
+
+            let aim = if flags & AIM != 0 {
+                let x = f32::from_le_bytes(reader.take()?);
+                let y = f32::from_le_bytes(reader.take()?);
+                Some(Vec2::new(x, y))
+            } else {
+                None
+            };
+            let upgrade = if flags & UPGRADE != 0 {
+                let [upgrade] = reader.take::<1>()?;
+                Some(upgrade)
+            } else {
+                None
+            };
+            let weapon = if flags & WEAPON != 0 {
+                let [weapon] = reader.take::<1>()?;
+                Some(weapon)
+            } else {
+                None
+            };
+
+            let input = PlayerInput {
+                movement: Vec2::new(axis(RIGHT, LEFT), axis(UP, DOWN)),
+                aim,
+                fire: flags & FIRE != 0,
+                reload: flags & RELOAD != 0,
+                upgrade,
+                weapon,
+            };
+
+            // The count comes straight from the file, a corrupt one could claim billions of ticks
+            if inputs.len() + count as usize > REPLAY_MAX_TICKS {
+                return Err(ReplayError::TooLong);
+            }
+
+            inputs.resize(inputs.len() + count as usize, input);
+        }
+
+        Ok(Self { seed, map, inputs })
+    }
+}
+
+struct ByteReader<'a>(&'a [u8]);
+
+impl ByteReader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+        let (bytes, rest) = self
+            .0
+            .split_first_chunk::<N>()
+            .ok_or(ReplayError::Truncated)?;
+
+        self.0 = rest;
+
+        Ok(*bytes)
+    }
+
+    fn take_slice(&mut self, length: usize) -> Result<&[u8], ReplayError> {
+        if self.0.len() < length {
+            return Err(ReplayError::Truncated);
+        }
+
+        let (bytes, rest) = self.0.split_at(length);
+        self.0 = rest;
+
+        Ok(bytes)
+    }
+}
+
+/// Next tick to feed back during playback.
+#[derive(Resource, Default)]
+pub(crate) struct PlaybackTick(usize);
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayMode>()
+            .init_resource::<Replay>()
+            .init_resource::<PlaybackTick>()
+            .add_systems(
+                OnEnter(GameState::Bootstraping),
+                start_replay.after(reset_game_rng).before(generate_tilemap),
+            )
+            .add_systems(
+                FixedPreUpdate,
+                play_back_input
+                    .after(PlayerInputSet)
+                    .run_if(in_state(PlayState::Running).and_then(is_playing_back)),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                record_input.run_if(in_state(PlayState::Running).and_then(is_recording)),
+            )
+            .add_systems(OnExit(GameState::Playing), save_replay.run_if(is_recording));
+    }
+}
+
+fn is_recording(mode: Res<ReplayMode>) -> bool {
+    matches!(*mode, ReplayMode::Record(_))
+}
+
+fn is_playing_back(mode: Res<ReplayMode>) -> bool {
+    matches!(*mode, ReplayMode::Playback)
+}
+
+fn start_replay(
+    mode: Res<ReplayMode>,
+    rng: Res<GameRng>,
+    mut replay: ResMut<Replay>,
+    mut selected: ResMut<SelectedMap>,
+    mut tick: ResMut<PlaybackTick>,
+) {
+    tick.0 = 0;
+
+    match *mode {
+        ReplayMode::Off => {}
+        ReplayMode::Record(_) => {
+            *replay = Replay {
+                seed: rng.seed(),
+                map: selected.0.clone(),
+                inputs: Vec::new(),
+            };
+        }
+        ReplayMode::Playback => selected.0 = replay.map.clone(),
+    }
+}
+
+pub(crate) fn play_back_input(
+    replay: Res<Replay>,
+    mut tick: ResMut<PlaybackTick>,
+    mut input: ResMut<PlayerInput>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if let Some(recorded) = replay.inputs.get(tick.0) {
+        *input = *recorded;
+        tick.0 += 1;
+    }
+
+    // The recorded run ended on this tick
+    if tick.0 >= replay.inputs.len() {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn record_input(input: Res<PlayerInput>, mut replay: ResMut<Replay>) {
+    replay.inputs.push(*input);
+}
+
+fn save_replay(mode: Res<ReplayMode>, replay: Res<Replay>) {
+    let ReplayMode::Record(path) = &*mode else {
+        return;
+    };
+
+    match replay.save(path) {
+        Ok(()) => info!("Saved replay to {}", path.display()),
+        Err(err) => error!("Could not save replay to {}: {err}", path.display()),
+    }
+}
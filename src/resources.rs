@@ -1,21 +1,18 @@
 use bevy::{prelude::*, window::PrimaryWindow};
 
-use crate::{constants::*, state::GameState};
+use crate::{
+    constants::*,
+    state::{GameState, PlayState},
+};
 
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct GlobalTextureAtlas {
     pub layout: Option<Handle<TextureAtlasLayout>>,
     pub image: Option<Handle<Image>>,
 }
 
-impl Default for GlobalTextureAtlas {
-    fn default() -> Self {
-        Self {
-            layout: None,
-            image: None,
-        }
-    }
-}
+#[derive(Resource, Default)]
+pub struct GlobalFont(pub Handle<Font>);
 
 #[derive(Resource)]
 pub struct CursorPosition(pub Option<Vec2>);
@@ -25,8 +22,12 @@ pub struct ResourcesPlugin;
 impl Plugin for ResourcesPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(GlobalTextureAtlas::default())
+            .insert_resource(GlobalFont::default())
             .insert_resource(CursorPosition(None))
             .add_systems(OnEnter(GameState::Loading), load_assets)
+            .add_systems(OnExit(PlayState::Running), pause_game_time)
+            .add_systems(OnEnter(PlayState::Running), resume_game_time)
+            .add_systems(OnExit(GameState::Playing), resume_game_time)
             .add_systems(
                 Update,
                 update_cursor_position.run_if(in_state(GameState::Playing)),
@@ -36,11 +37,13 @@ impl Plugin for ResourcesPlugin {
 
 fn load_assets(
     mut handle: ResMut<GlobalTextureAtlas>,
+    mut font: ResMut<GlobalFont>,
     asset_server: Res<AssetServer>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
     handle.image = Some(asset_server.load(SPRITE_SHEET_PATH));
+    font.0 = asset_server.load(FONT_PATH);
 
     let texture_atlas_layout = TextureAtlasLayout::from_grid(
         UVec2::new(TILE_WIDTH as u32, TILE_HEIGHT as u32),
@@ -55,6 +58,14 @@ fn load_assets(
     next_state.set(GameState::Menu)
 }
 
+fn pause_game_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn resume_game_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
 fn update_cursor_position(
     mut cursor_position: ResMut<CursorPosition>,
     window_query: Query<&Window, With<PrimaryWindow>>,
@@ -62,6 +73,7 @@ fn update_cursor_position(
 ) {
     if window_query.is_empty() || camera_query.is_empty() {
         cursor_position.0 = None;
+        return;
     }
 
     let (camera, camera_transform) = camera_query.single();
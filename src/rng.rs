@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::state::GameState;
+
+pub struct RngPlugin;
+
+/// Seed every run starts from, a fresh one is rolled per run when unset.
+#[derive(Resource, Default)]
+pub struct RngSeed(pub Option<u64>);
+
+/// Independent random sequences, so drawing more numbers in one system
+/// doesn't shift the results of another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RngStream {
+    World,
+    Spawning,
+    Weapons,
+    Upgrades,
+    Drops,
+}
+
+impl RngStream {
+    pub const ALL: [RngStream; 5] = [
+        RngStream::World,
+        RngStream::Spawning,
+        RngStream::Weapons,
+        RngStream::Upgrades,
+        RngStream::Drops,
+    ];
+}
+
+/// Source of all gameplay randomness, reseeded at the start of every run.
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    streams: Vec<ChaCha8Rng>,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        let streams = RngStream::ALL
+            .iter()
+            .map(|stream| {
+                let mut rng = ChaCha8Rng::seed_from_u64(seed);
+                rng.set_stream(*stream as u64);
+                rng
+            })
+            .collect();
+
+        Self { seed, streams }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn stream(&mut self, stream: RngStream) -> &mut ChaCha8Rng {
+        &mut self.streams[stream as usize]
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RngSeed>()
+            .init_resource::<GameRng>()
+            .add_systems(OnEnter(GameState::Bootstraping), reset_game_rng);
+    }
+}
+
+/// Runs before anything else spawned on `OnEnter(GameState::Bootstraping)`.
+pub fn reset_game_rng(seed: Res<RngSeed>, mut rng: ResMut<GameRng>) {
+    *rng = GameRng::new(seed.0.unwrap_or_else(rand::random));
+}
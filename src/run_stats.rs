@@ -0,0 +1,98 @@
+use bevy::{ecs::entity::EntityHashSet, prelude::*};
+
+use crate::{
+    enemy::{despawn_dead_enemies, EnemyKilledEvent},
+    schedule::GameplaySet,
+    state::GameState,
+    wave::WaveCleared,
+    weapon::{despawn_old_projectiles, Projectile, ProjectileHitEvent},
+};
+
+pub struct RunStatsPlugin;
+
+/// Statistics of the current run, shown on the game over screen.
+#[derive(Resource, Default)]
+pub struct RunStats {
+    pub time_survived: f32,
+    pub kills: u32,
+    pub damage_dealt: f32,
+    pub projectiles_fired: u32,
+    pub projectiles_hit: u32,
+    pub waves_cleared: u32,
+    /// Live projectiles that already counted towards `projectiles_hit`.
+    hit_projectiles: EntityHashSet,
+}
+
+impl RunStats {
+    /// Share of fired projectiles that hit at least one enemy.
+    pub fn accuracy(&self) -> f32 {
+        if self.projectiles_fired == 0 {
+            return 0.0;
+        }
+
+        self.projectiles_hit as f32 / self.projectiles_fired as f32
+    }
+}
+
+impl Plugin for RunStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .add_systems(OnEnter(GameState::Bootstraping), reset_run_stats)
+            .add_systems(
+                FixedUpdate,
+                (
+                    update_time_survived,
+                    count_fired_projectiles,
+                    count_projectile_hits,
+                    count_kills,
+                    count_cleared_waves,
+                )
+                    .chain()
+                    .in_set(GameplaySet::Cleanup)
+                    .after(despawn_dead_enemies)
+                    .after(despawn_old_projectiles)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn reset_run_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+fn update_time_survived(time: Res<Time>, mut stats: ResMut<RunStats>) {
+    stats.time_survived += time.delta_seconds();
+}
+
+fn count_fired_projectiles(
+    mut stats: ResMut<RunStats>,
+    projectile_query: Query<(), Added<Projectile>>,
+) {
+    stats.projectiles_fired += projectile_query.iter().count() as u32;
+}
+
+fn count_projectile_hits(
+    mut stats: ResMut<RunStats>,
+    mut events: EventReader<ProjectileHitEvent>,
+    mut removed: RemovedComponents<Projectile>,
+) {
+    for event in events.read() {
+        stats.damage_dealt += event.damage;
+
+        if stats.hit_projectiles.insert(event.projectile) {
+            stats.projectiles_hit += 1;
+        }
+    }
+
+    for e in removed.read() {
+        stats.hit_projectiles.remove(&e);
+    }
+}
+
+fn count_kills(mut stats: ResMut<RunStats>, mut events: EventReader<EnemyKilledEvent>) {
+    stats.kills += events.read().count() as u32;
+}
+
+fn count_cleared_waves(mut stats: ResMut<RunStats>, mut events: EventReader<WaveCleared>) {
+    stats.waves_cleared += events.read().count() as u32;
+}
@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+
+use crate::{collision::CollisionSet, navigation::NavigationSet};
+
+pub struct SchedulePlugin;
+
+/// Phases of a gameplay tick in `FixedUpdate`, run one after the other.
+///
+/// Player input is sampled before them in `FixedPreUpdate`. Systems touching the same data
+/// inside a phase are ordered too, so a seed and an input stream always play out the same run.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameplaySet {
+    /// Modifier timers, the wave director and new enemies.
+    Spawn,
+    /// The `FlowField` towards the player.
+    Navigation,
+    /// Moves the player, enemies and projectiles.
+    Movement,
+    /// Resolves overlaps between everything that moved.
+    Collision,
+    /// Hits, attacks and deaths.
+    Damage,
+    /// Despawns expired entities and tallies the run statistics.
+    Cleanup,
+}
+
+impl Plugin for SchedulePlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            FixedUpdate,
+            (
+                GameplaySet::Spawn,
+                GameplaySet::Navigation,
+                GameplaySet::Movement,
+                GameplaySet::Collision,
+                GameplaySet::Damage,
+                GameplaySet::Cleanup,
+            )
+                .chain(),
+        )
+        .configure_sets(
+            FixedUpdate,
+            (
+                NavigationSet.in_set(GameplaySet::Navigation),
+                CollisionSet.in_set(GameplaySet::Collision),
+            ),
+        );
+    }
+}
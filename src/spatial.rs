@@ -0,0 +1,78 @@
+use bevy::{prelude::*, utils::HashMap};
+
+/// Uniform grid bucketing entities by position.
+///
+/// Inserting is a hash and a push, so the whole grid is cheap enough to rebuild every
+/// simulation tick. Cleared buckets keep their allocations between rebuilds.
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<(Entity, Vec2)>>,
+    len: usize,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+            len: 0,
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn cell_at(&self, position: Vec2) -> IVec2 {
+        (position / self.cell_size).floor().as_ivec2()
+    }
+
+    pub fn clear(&mut self) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+
+        self.len = 0;
+    }
+
+    pub fn insert(&mut self, entity: Entity, position: Vec2) {
+        let cell = self.cell_at(position);
+
+        self.cells.entry(cell).or_default().push((entity, position));
+        self.len += 1;
+    }
+
+    /// Non-empty cells and their contents.
+    pub fn cells(&self) -> impl Iterator<Item = (IVec2, &[(Entity, Vec2)])> {
+        self.cells
+            .iter()
+            .filter(|(_, items)| !items.is_empty())
+            .map(|(cell, items)| (*cell, items.as_slice()))
+    }
+
+    /// Entities whose position lies within `radius` of `center`.
+    pub fn within_radius(
+        &self,
+        center: Vec2,
+        radius: f32,
+    ) -> impl Iterator<Item = (Entity, Vec2)> + '_ {
+        let min = self.cell_at(center - Vec2::splat(radius));
+        let max = self.cell_at(center + Vec2::splat(radius));
+        let radius_squared = radius * radius;
+
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .filter(move |(_, position)| position.distance_squared(center) <= radius_squared)
+    }
+}
@@ -7,4 +7,19 @@ pub enum GameState {
     Menu,
     Bootstraping,
     Playing,
+    GameOver,
+}
+
+/// Whether the simulation is advancing while `GameState::Playing`.
+///
+/// Game time is paused in every state other than `Running`.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, SubStates)]
+#[source(GameState = GameState::Playing)]
+pub enum PlayState {
+    #[default]
+    Running,
+    Paused,
+    Settings,
+    /// Waiting for the player to pick an upgrade.
+    LevelUp,
 }
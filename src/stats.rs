@@ -0,0 +1,185 @@
+use bevy::prelude::*;
+
+use crate::{player::Health, schedule::GameplaySet, state::GameState};
+
+pub struct StatsPlugin;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stat {
+    MaxHealth,
+    /// Units per second.
+    Speed,
+    /// Contact damage for enemies, projectile damage for the player's weapons.
+    Damage,
+    /// Shots per second.
+    FireRate,
+    /// Projectiles per shot.
+    Pellets,
+    /// Weapons circling the player and firing on their own.
+    OrbitingWeapons,
+}
+
+impl Stat {
+    pub const ALL: [Stat; 6] = [
+        Stat::MaxHealth,
+        Stat::Speed,
+        Stat::Damage,
+        Stat::FireRate,
+        Stat::Pellets,
+        Stat::OrbitingWeapons,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModifierKind {
+    /// Added to the base value.
+    Add(f32),
+    /// Scales the value after every addition, stacks multiplicatively.
+    Multiply(f32),
+}
+
+/// What granted a modifier, so it can be removed again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModifierSource {
+    Upgrade,
+    Pickup,
+}
+
+#[derive(Debug, Clone)]
+pub struct Modifier {
+    pub stat: Stat,
+    pub kind: ModifierKind,
+    pub source: ModifierSource,
+    /// Removed once the timer finishes, permanent when `None`.
+    pub duration: Option<Timer>,
+}
+
+impl Modifier {
+    pub fn add(stat: Stat, value: f32, source: ModifierSource) -> Self {
+        Self {
+            stat,
+            kind: ModifierKind::Add(value),
+            source,
+            duration: None,
+        }
+    }
+
+    pub fn multiply(stat: Stat, factor: f32, source: ModifierSource) -> Self {
+        Self {
+            stat,
+            kind: ModifierKind::Multiply(factor),
+            source,
+            duration: None,
+        }
+    }
+
+    pub fn lasting(mut self, seconds: f32) -> Self {
+        self.duration = Some(Timer::from_seconds(seconds, TimerMode::Once));
+        self
+    }
+}
+
+/// Base values of an entity's stats and the modifiers stacked on top of them.
+///
+/// Systems read the effective value with `get`, or `apply` the modifiers to a base defined
+/// elsewhere, like a weapon definition.
+#[derive(Component, Debug, Clone, Default)]
+pub struct Stats {
+    base: [f32; Stat::ALL.len()],
+    modifiers: Vec<Modifier>,
+}
+
+impl Stats {
+    pub fn with_base(mut self, stat: Stat, value: f32) -> Self {
+        self.set_base(stat, value);
+        self
+    }
+
+    pub fn base(&self, stat: Stat) -> f32 {
+        self.base[stat as usize]
+    }
+
+    pub fn set_base(&mut self, stat: Stat, value: f32) {
+        self.base[stat as usize] = value;
+    }
+
+    pub fn get(&self, stat: Stat) -> f32 {
+        self.apply(stat, self.base(stat))
+    }
+
+    /// `base` with every modifier of `stat` applied, additions first.
+    pub fn apply(&self, stat: Stat, base: f32) -> f32 {
+        let modifiers = self
+            .modifiers
+            .iter()
+            .filter(|modifier| modifier.stat == stat);
+
+        let (added, factor) =
+            modifiers.fold((0.0, 1.0), |(added, factor), modifier| {
+                match modifier.kind {
+                    ModifierKind::Add(value) => (added + value, factor),
+                    ModifierKind::Multiply(value) => (added, factor * value),
+                }
+            });
+
+        (base + added) * factor
+    }
+
+    pub fn modifiers(&self) -> &[Modifier] {
+        &self.modifiers
+    }
+
+    pub fn add_modifier(&mut self, modifier: Modifier) {
+        self.modifiers.push(modifier);
+    }
+
+    /// Like `add_modifier`, but an identical modifier already in place is replaced, restarting
+    /// its duration, instead of stacking another one.
+    pub fn refresh_modifier(&mut self, modifier: Modifier) {
+        let existing = self.modifiers.iter_mut().find(|existing| {
+            existing.stat == modifier.stat
+                && existing.kind == modifier.kind
+                && existing.source == modifier.source
+        });
+
+        match existing {
+            Some(existing) => *existing = modifier,
+            None => self.modifiers.push(modifier),
+        }
+    }
+
+    pub fn remove_source(&mut self, source: ModifierSource) {
+        self.modifiers.retain(|modifier| modifier.source != source);
+    }
+
+    /// Advances the modifier durations and drops the expired ones.
+    pub fn tick(&mut self, delta: std::time::Duration) {
+        self.modifiers
+            .retain_mut(|modifier| match &mut modifier.duration {
+                Some(timer) => !timer.tick(delta).finished(),
+                None => true,
+            });
+    }
+}
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            tick_modifiers
+                .in_set(GameplaySet::Spawn)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn tick_modifiers(time: Res<Time>, mut query: Query<(&mut Stats, Option<&mut Health>)>) {
+    for (mut stats, health) in query.iter_mut() {
+        stats.tick(time.delta());
+
+        // A temporary max health boost ran out
+        if let Some(mut health) = health {
+            health.0 = health.0.min(stats.get(Stat::MaxHealth));
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+
+use crate::{collision::Collider, spatial::SpatialGrid};
+
+/// Current movement of a steered entity, in units per second.
+#[derive(Component, Default)]
+pub struct Velocity(pub Vec2);
+
+/// Relative strength of each steering behaviour, tweakable at runtime.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct SteeringWeights {
+    /// Pull towards the behaviour's target, e.g. the player.
+    pub seek: f32,
+    /// Push away from neighbours that are too close.
+    pub separation: f32,
+    /// Match the heading of nearby neighbours.
+    pub alignment: f32,
+    /// Push away from solid obstacles ahead.
+    pub obstacle_avoidance: f32,
+    /// How quickly the velocity turns towards the desired one, per second.
+    pub responsiveness: f32,
+}
+
+impl Default for SteeringWeights {
+    fn default() -> Self {
+        Self {
+            seek: 1.0,
+            separation: 1.5,
+            alignment: 0.3,
+            obstacle_avoidance: 2.0,
+            responsiveness: 8.0,
+        }
+    }
+}
+
+/// Sum of pushes away from every neighbour within `radius`, stronger the closer they are.
+pub fn separation(
+    position: Vec2,
+    radius: f32,
+    neighbours: impl Iterator<Item = (Entity, Vec2)>,
+    this: Entity,
+) -> Vec2 {
+    neighbours
+        .filter(|(e, _)| *e != this)
+        .fold(Vec2::ZERO, |force, (_, other)| {
+            let offset = position - other;
+            let distance = offset.length();
+
+            if distance <= f32::EPSILON {
+                return force;
+            }
+
+            force + offset / distance * (1.0 - distance / radius).max(0.0)
+        })
+}
+
+/// Average heading of the given neighbour velocities, normalized.
+pub fn alignment(velocities: impl Iterator<Item = Vec2>) -> Vec2 {
+    let (sum, count) = velocities.fold((Vec2::ZERO, 0), |(sum, count), velocity| {
+        (sum + velocity.normalize_or_zero(), count + 1)
+    });
+
+    if count == 0 {
+        return Vec2::ZERO;
+    }
+
+    (sum / count as f32).normalize_or_zero()
+}
+
+/// Push away from the surface of every obstacle within `radius`, stronger the closer it is.
+pub fn obstacle_avoidance(
+    position: Vec2,
+    radius: f32,
+    obstacles: &SpatialGrid,
+    colliders: impl Fn(Entity) -> Option<Collider>,
+) -> Vec2 {
+    let mut force = Vec2::ZERO;
+
+    for (entity, obstacle_position) in obstacles.within_radius(position, radius * 2.0) {
+        let Some(collider) = colliders(entity) else {
+            continue;
+        };
+
+        let closest = match collider {
+            Collider::Circle { radius } => {
+                obstacle_position + (position - obstacle_position).clamp_length_max(radius)
+            }
+            Collider::Aabb { half_extents } => position.clamp(
+                obstacle_position - half_extents,
+                obstacle_position + half_extents,
+            ),
+        };
+
+        let offset = position - closest;
+        let distance = offset.length();
+
+        if distance <= f32::EPSILON || distance >= radius {
+            continue;
+        }
+
+        force += offset / distance * (1.0 - distance / radius);
+    }
+
+    force
+}
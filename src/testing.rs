@@ -0,0 +1,357 @@
+//! Drives the gameplay plugins tick by tick from integration tests.
+
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+
+use crate::{
+    enemy::{spawn_enemy, Enemy, EnemyType},
+    experience::spawn_xp_gem,
+    headless::headless_app_with,
+    input::{PlayerInput, PlayerInputSet},
+    interpolation::InterpolatedTranslation,
+    map::{MapDefinition, SelectedMap},
+    pickup::{spawn_pickup, PickupKind},
+    player::{Health, Player},
+    replay::{Replay, ReplayMode},
+    rng::RngSeed,
+    state::GameState,
+    tilemap::{spawn_solid_tile, TileMap, TileMapGenerator},
+    wave::{WaveCleared, WavePlugin, WaveStarted},
+    weapon::{spawn_projectile, WeaponDefinition},
+    GameplayPlugins, GlobalTextureAtlas, SOLID_TILES,
+};
+
+const TEST_SEED: u64 = 0;
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Headless game already in `GameState::Playing`, every `tick` runs one fixed update.
+///
+/// The generated map has no solid tiles, tests place their own with `spawn_solid_tile`.
+pub struct TestApp {
+    pub app: App,
+}
+
+/// Replaces the sampled input devices while set.
+#[derive(Resource, Default)]
+struct ScriptedInput(Option<PlayerInput>);
+
+/// Every event of type `E` sent since `TestApp::record_events::<E>` was called.
+#[derive(Resource)]
+struct RecordedEvents<E: Event> {
+    events: Vec<E>,
+    reader: ManualEventReader<E>,
+}
+
+impl TestApp {
+    /// Gameplay without the wave director, only entities spawned by the test are around.
+    pub fn new() -> Self {
+        let mut app = headless_app_with(GameplayPlugins.build().disable::<WavePlugin>());
+
+        // Run stats still listen for cleared waves
+        app.add_event::<WaveStarted>().add_event::<WaveCleared>();
+
+        Self::start(app)
+    }
+
+    pub fn with_waves() -> Self {
+        Self::start(headless_app_with(GameplayPlugins))
+    }
+
+    /// Gameplay with waves on a hand-authored map.
+    pub fn with_map(map: MapDefinition) -> Self {
+        let mut app = headless_app_with(GameplayPlugins);
+
+        app.insert_resource(SelectedMap(Some(map)));
+
+        Self::start(app)
+    }
+
+    /// Gameplay with waves on `map`, the run is saved as a replay to `path` once it ends.
+    pub fn recording(map: MapDefinition, path: PathBuf) -> Self {
+        let mut app = headless_app_with(GameplayPlugins);
+
+        app.insert_resource(SelectedMap(Some(map)))
+            .insert_resource(ReplayMode::Record(path));
+
+        Self::start(app)
+    }
+
+    /// Gameplay with waves fed by `replay`, on the map it was recorded on.
+    pub fn playing_back(replay: Replay) -> Self {
+        let mut app = headless_app_with(GameplayPlugins);
+        let seed = replay.seed;
+
+        app.insert_resource(ReplayMode::Playback)
+            .insert_resource(replay);
+
+        Self::start_with_seed(app, seed)
+    }
+
+    fn start(app: App) -> Self {
+        Self::start_with_seed(app, TEST_SEED)
+    }
+
+    fn start_with_seed(mut app: App, seed: u64) -> Self {
+        app.insert_resource(RngSeed(Some(seed)))
+            .insert_resource(TileMapGenerator {
+                solid_clusters: 0,
+                ..default()
+            })
+            .init_resource::<ScriptedInput>()
+            .add_systems(FixedPreUpdate, apply_scripted_input.after(PlayerInputSet));
+
+        // The run starts once the weapon definition has loaded from disk
+        let started = Instant::now();
+
+        while *app.world().resource::<State<GameState>>() != GameState::Playing {
+            assert!(
+                started.elapsed() < STARTUP_TIMEOUT,
+                "game did not start within {STARTUP_TIMEOUT:?}"
+            );
+
+            app.update();
+        }
+
+        Self { app }
+    }
+
+    pub fn tick(&mut self) {
+        self.app.update();
+    }
+
+    pub fn advance(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            self.tick();
+        }
+    }
+
+    pub fn state(&self) -> GameState {
+        *self.app.world().resource::<State<GameState>>().get()
+    }
+
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    pub fn exists(&self, entity: Entity) -> bool {
+        self.world().get_entity(entity).is_some()
+    }
+
+    /// Input used by every following tick instead of the keyboard and mouse.
+    pub fn set_input(&mut self, input: PlayerInput) {
+        self.world_mut().resource_mut::<ScriptedInput>().0 = Some(input);
+    }
+
+    pub fn player(&mut self) -> Entity {
+        self.world_mut()
+            .query_filtered::<Entity, With<Player>>()
+            .single(self.world())
+    }
+
+    pub fn player_health(&mut self) -> f32 {
+        let player = self.player();
+
+        self.world().get::<Health>(player).unwrap().0
+    }
+
+    pub fn set_player_health(&mut self, health: f32) {
+        let player = self.player();
+
+        self.world_mut().get_mut::<Health>(player).unwrap().0 = health;
+    }
+
+    pub fn player_position(&mut self) -> Vec2 {
+        let player = self.player();
+
+        self.position(player)
+    }
+
+    pub fn set_player_position(&mut self, position: Vec2) {
+        let player = self.player();
+
+        self.set_position(player, position);
+    }
+
+    /// Simulated position, ignoring render interpolation.
+    pub fn position(&self, entity: Entity) -> Vec2 {
+        let entity = self.world().entity(entity);
+
+        match entity.get::<InterpolatedTranslation>() {
+            Some(interpolated) => interpolated.current().truncate(),
+            None => entity.get::<Transform>().unwrap().translation.truncate(),
+        }
+    }
+
+    pub fn set_position(&mut self, entity: Entity, position: Vec2) {
+        let mut entity = self.world_mut().entity_mut(entity);
+        let mut transform = entity.get_mut::<Transform>().unwrap();
+
+        transform.translation = position.extend(transform.translation.z);
+
+        let translation = transform.translation;
+
+        if let Some(mut interpolated) = entity.get_mut::<InterpolatedTranslation>() {
+            interpolated.snap(translation);
+        }
+    }
+
+    pub fn spawn_enemy(&mut self, kind: EnemyType, position: Vec2) -> Entity {
+        let world = self.world_mut();
+        let entity = spawn_enemy(
+            &mut world.commands(),
+            &GlobalTextureAtlas::default(),
+            kind,
+            position.extend(1.0),
+        );
+
+        world.flush();
+        entity
+    }
+
+    pub fn enemy_health(&self, entity: Entity) -> Option<f32> {
+        self.world().get::<Enemy>(entity).map(|enemy| enemy.health)
+    }
+
+    pub fn enemies(&mut self) -> Vec<Entity> {
+        self.world_mut()
+            .query_filtered::<Entity, With<Enemy>>()
+            .iter(self.world())
+            .collect()
+    }
+
+    /// Solid tile covering `position`, snapped to the tile grid and added to the `TileMap`.
+    pub fn spawn_solid_tile(&mut self, position: Vec2) -> Entity {
+        let world = self.world_mut();
+        let mut map = world.resource_mut::<TileMap>();
+        let tile = map.tile_at(position).expect("solid tile outside the map");
+        let index = map.index(tile);
+
+        map.solid[index] = Some(SOLID_TILES[0]);
+
+        let position = map.tile_center(tile);
+        let entity = spawn_solid_tile(
+            &mut world.commands(),
+            &GlobalTextureAtlas::default(),
+            SOLID_TILES[0],
+            position,
+        );
+
+        world.flush();
+        entity
+    }
+
+    pub fn spawn_xp_gem(&mut self, xp: u32, position: Vec2) -> Entity {
+        let world = self.world_mut();
+        let entity = spawn_xp_gem(
+            &mut world.commands(),
+            &GlobalTextureAtlas::default(),
+            xp,
+            position,
+        );
+
+        world.flush();
+        entity
+    }
+
+    pub fn spawn_pickup(&mut self, kind: PickupKind, position: Vec2) -> Entity {
+        let world = self.world_mut();
+        let entity = spawn_pickup(
+            &mut world.commands(),
+            &GlobalTextureAtlas::default(),
+            kind,
+            position,
+        );
+
+        world.flush();
+        entity
+    }
+
+    pub fn spawn_projectile(
+        &mut self,
+        definition: &WeaponDefinition,
+        position: Vec2,
+        direction: Vec2,
+    ) -> Entity {
+        let world = self.world_mut();
+        let entity = spawn_projectile(
+            &mut world.commands(),
+            &GlobalTextureAtlas::default(),
+            definition,
+            definition.projectile_damage,
+            position.extend(1.0),
+            direction.extend(0.0),
+        );
+
+        world.flush();
+        entity
+    }
+
+    /// Starts collecting events of type `E`, read them back with `events`.
+    pub fn record_events<E: Event + Clone>(&mut self) {
+        let reader = self.world().resource::<Events<E>>().get_reader_current();
+
+        self.app
+            .insert_resource(RecordedEvents::<E> {
+                events: Vec::new(),
+                reader,
+            })
+            .add_systems(Last, record_events::<E>);
+    }
+
+    pub fn events<E: Event + Clone>(&self) -> &[E] {
+        &self
+            .world()
+            .get_resource::<RecordedEvents<E>>()
+            .expect("call record_events before reading events")
+            .events
+    }
+}
+
+impl Default for TestApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Single accurate shot per second without a magazine, tests override the fields they need.
+pub fn weapon_definition() -> WeaponDefinition {
+    WeaponDefinition {
+        name: "Test".to_string(),
+        fire_rate: 1.0,
+        pellets_per_shot: 1,
+        spread: 0.0,
+        projectile_speed: 600.0,
+        projectile_damage: 10.0,
+        projectile_lifetime: 1.0,
+        projectile_pierce: 0,
+        magazine_size: None,
+        reload_time: 0.0,
+        sprite_index: 17,
+        projectile_sprite_index: 16,
+    }
+}
+
+fn apply_scripted_input(scripted: Res<ScriptedInput>, mut input: ResMut<PlayerInput>) {
+    if let Some(scripted) = scripted.0 {
+        *input = scripted;
+    }
+}
+
+fn record_events<E: Event + Clone>(
+    mut recorded: ResMut<RecordedEvents<E>>,
+    events: Res<Events<E>>,
+) {
+    let recorded = recorded.as_mut();
+
+    recorded
+        .events
+        .extend(recorded.reader.read(&events).cloned());
+}
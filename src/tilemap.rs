@@ -0,0 +1,276 @@
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    collision::{Collider, Obstacle},
+    constants::*,
+    map::{MapDefinition, SelectedMap},
+    rng::{reset_game_rng, GameRng, RngStream},
+    state::GameState,
+    world::{GameEntity, WorldBounds},
+    GlobalTextureAtlas,
+};
+
+pub struct TileMapPlugin;
+
+/// Grid of atlas tiles covering the world, centered on the origin.
+///
+/// Every layer holds one optional atlas index per tile, row by row from the bottom left.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct TileMap {
+    pub width: u32,
+    pub height: u32,
+    pub ground: Vec<Option<usize>>,
+    pub decorations: Vec<Option<usize>>,
+    /// Tiles blocking the player, enemies and projectiles.
+    pub solid: Vec<Option<usize>>,
+    pub player_spawn: Vec2,
+    /// Areas enemies spawn in, anywhere around the player when empty.
+    pub enemy_spawn_zones: Vec<Rect>,
+    pub pickup_locations: Vec<Vec2>,
+}
+
+/// Settings for the procedurally generated map.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct TileMapGenerator {
+    pub ground: usize,
+    pub decorations: usize,
+    pub solid_clusters: usize,
+    pub solid_cluster_size: usize,
+    /// Radius around the player spawn kept free of solid tiles.
+    pub spawn_clearance: f32,
+}
+
+impl Default for TileMapGenerator {
+    fn default() -> Self {
+        Self {
+            ground: NUM_WORLD_GROUND_TILES,
+            decorations: NUM_WORLD_DECORATIONS,
+            solid_clusters: NUM_WORLD_SOLID_CLUSTERS,
+            solid_cluster_size: WORLD_SOLID_CLUSTER_SIZE,
+            spawn_clearance: WORLD_SPAWN_CLEARANCE,
+        }
+    }
+}
+
+impl TileMap {
+    pub fn new(width: u32, height: u32) -> Self {
+        let len = (width * height) as usize;
+
+        Self {
+            width,
+            height,
+            ground: vec![None; len],
+            decorations: vec![None; len],
+            solid: vec![None; len],
+            ..default()
+        }
+    }
+
+    pub fn from_definition(definition: &MapDefinition) -> Self {
+        let size = definition.size();
+        let mut map = Self::new(size.x, size.y);
+
+        // Definitions count rows from the top, the map from the bottom
+        let tile = |(x, y): (u32, u32)| UVec2::new(x, size.y - 1 - y);
+        let index = |position: UVec2| ((size.y - 1 - position.y) * size.x + position.x) as usize;
+
+        let layers = [
+            (&definition.ground, &mut map.ground),
+            (&definition.decorations, &mut map.decorations),
+            (&definition.solid, &mut map.solid),
+        ];
+
+        for (rows, layer) in layers {
+            for (position, sprite) in definition.tiles(rows) {
+                layer[index(position)] = Some(sprite);
+            }
+        }
+
+        map.player_spawn = map.tile_center(tile(definition.player_spawn));
+        map.enemy_spawn_zones = definition
+            .enemy_spawn_zones
+            .iter()
+            .map(|zone| {
+                Rect::from_corners(
+                    map.tile_center(tile(zone.min)),
+                    map.tile_center(tile(zone.max)),
+                )
+            })
+            .collect();
+        map.pickup_locations = definition
+            .pickups
+            .iter()
+            .map(|pickup| map.tile_center(tile(*pickup)))
+            .collect();
+
+        map
+    }
+
+    pub fn index(&self, tile: UVec2) -> usize {
+        (tile.y * self.width + tile.x) as usize
+    }
+
+    pub fn bounds(&self) -> Rect {
+        let half_size = UVec2::new(self.width, self.height).as_vec2() * WORLD_TILE_SIZE / 2.0;
+
+        Rect::from_center_half_size(Vec2::ZERO, half_size)
+    }
+
+    pub fn tile_center(&self, tile: UVec2) -> Vec2 {
+        self.bounds().min + (tile.as_vec2() + 0.5) * WORLD_TILE_SIZE
+    }
+
+    pub fn tile_at(&self, position: Vec2) -> Option<UVec2> {
+        let tile = ((position - self.bounds().min) / WORLD_TILE_SIZE).floor();
+
+        (tile.x >= 0.0
+            && tile.y >= 0.0
+            && tile.x < self.width as f32
+            && tile.y < self.height as f32)
+            .then(|| tile.as_uvec2())
+    }
+
+    pub fn is_solid(&self, tile: UVec2) -> bool {
+        self.solid[self.index(tile)].is_some()
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = UVec2> {
+        let width = self.width;
+
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| UVec2::new(x, y)))
+    }
+
+    /// Random map the size of the default world, with scattered ground and decorations and clusters
+    /// of solid tiles.
+    pub fn generate(generator: &TileMapGenerator, rng: &mut impl Rng) -> Self {
+        let width = (WORLD_W * 2.0 / WORLD_TILE_SIZE).ceil() as u32;
+        let height = (WORLD_H * 2.0 / WORLD_TILE_SIZE).ceil() as u32;
+        let mut map = Self::new(width, height);
+
+        for _ in 0..generator.solid_clusters {
+            let sprite = *SOLID_TILES.choose(rng).unwrap();
+            let mut tile = UVec2::new(rng.gen_range(0..width), rng.gen_range(0..height));
+
+            for _ in 0..generator.solid_cluster_size {
+                if map.tile_center(tile).length() > generator.spawn_clearance {
+                    let index = map.index(tile);
+                    map.solid[index] = Some(sprite);
+                }
+
+                // Wander to a neighbouring tile so clusters grow into short walls and clumps
+                let step = *[IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+                    .choose(rng)
+                    .unwrap();
+                tile = (tile.as_ivec2() + step)
+                    .clamp(IVec2::ZERO, IVec2::new(width as i32 - 1, height as i32 - 1))
+                    .as_uvec2();
+            }
+        }
+
+        for _ in 0..generator.decorations {
+            let tile = UVec2::new(rng.gen_range(0..width), rng.gen_range(0..height));
+            let index = map.index(tile);
+
+            if map.solid[index].is_none() {
+                map.decorations[index] = Some(*DECORATION_TILES.choose(rng).unwrap());
+            }
+        }
+
+        // Grass scattered over the open tiles, under nothing else
+        for _ in 0..generator.ground {
+            let tile = UVec2::new(rng.gen_range(0..width), rng.gen_range(0..height));
+            let index = map.index(tile);
+
+            if map.solid[index].is_none() && map.decorations[index].is_none() {
+                map.ground[index] = Some(*GROUND_TILES.choose(rng).unwrap());
+            }
+        }
+
+        map
+    }
+}
+
+impl Plugin for TileMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TileMap>()
+            .init_resource::<TileMapGenerator>()
+            .add_systems(
+                OnEnter(GameState::Bootstraping),
+                (generate_tilemap, spawn_tilemap)
+                    .chain()
+                    .after(reset_game_rng),
+            );
+    }
+}
+
+/// Builds the map for the next run and sizes the world to it.
+pub fn generate_tilemap(
+    selected: Res<SelectedMap>,
+    generator: Res<TileMapGenerator>,
+    mut rng: ResMut<GameRng>,
+    mut map: ResMut<TileMap>,
+    mut bounds: ResMut<WorldBounds>,
+) {
+    *map = match &selected.0 {
+        Some(definition) => TileMap::from_definition(definition),
+        None => TileMap::generate(&generator, rng.stream(RngStream::World)),
+    };
+    bounds.0 = map.bounds();
+}
+
+fn spawn_tilemap(mut commands: Commands, handle: Res<GlobalTextureAtlas>, map: Res<TileMap>) {
+    for tile in map.tiles() {
+        let index = map.index(tile);
+        let position = map.tile_center(tile);
+
+        let layers = [(map.ground[index], 0.0), (map.decorations[index], 0.5)];
+
+        for (sprite, z) in layers {
+            if let Some(sprite) = sprite {
+                commands.spawn((tile_sprite(&handle, sprite, position.extend(z)), GameEntity));
+            }
+        }
+
+        if let Some(sprite) = map.solid[index] {
+            spawn_solid_tile(&mut commands, &handle, sprite, position);
+        }
+    }
+}
+
+pub fn spawn_solid_tile(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    sprite: usize,
+    position: Vec2,
+) -> Entity {
+    commands
+        .spawn((
+            tile_sprite(handle, sprite, position.extend(1.0)),
+            Collider::Aabb {
+                half_extents: Vec2::splat(WORLD_TILE_SIZE / 2.0),
+            },
+            Obstacle,
+            GameEntity,
+        ))
+        .id()
+}
+
+fn tile_sprite(
+    handle: &GlobalTextureAtlas,
+    sprite: usize,
+    translation: Vec3,
+) -> (SpriteBundle, TextureAtlas) {
+    (
+        SpriteBundle {
+            texture: handle.image.clone().unwrap_or_default(),
+            transform: Transform::from_translation(translation)
+                .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+            ..default()
+        },
+        TextureAtlas {
+            layout: handle.layout.clone().unwrap_or_default(),
+            index: sprite,
+        },
+    )
+}
@@ -0,0 +1,241 @@
+use bevy::{prelude::*, time::Stopwatch};
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+
+use crate::{
+    enemy::{get_random_spawn_position, spawn_enemy, Enemy, EnemyType},
+    player::Player,
+    rng::{GameRng, RngStream},
+    schedule::GameplaySet,
+    state::GameState,
+    tilemap::TileMap,
+    world::WorldBounds,
+    *,
+};
+
+pub struct WavePlugin;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WavePhase {
+    /// Waiting for the next wave to start.
+    Resting,
+    /// Spending the wave budget on new enemies.
+    Spawning,
+    /// Budget spent, waiting for the player to clear the remaining enemies.
+    Fighting,
+}
+
+#[derive(Resource)]
+pub struct WaveDirector {
+    pub wave: u32,
+    pub phase: WavePhase,
+    budget: u32,
+    boss_pending: bool,
+    rest_timer: Timer,
+    spawn_timer: Timer,
+    wave_timer: Stopwatch,
+    /// Budget multiplier based on how quickly the player cleared the last wave.
+    performance: f32,
+}
+
+impl Default for WaveDirector {
+    fn default() -> Self {
+        Self {
+            wave: 0,
+            phase: WavePhase::Resting,
+            budget: 0,
+            boss_pending: false,
+            rest_timer: Timer::from_seconds(WAVE_FIRST_DELAY, TimerMode::Once),
+            spawn_timer: Timer::from_seconds(ENEMY_SPAWN_INTERVAL, TimerMode::Repeating),
+            wave_timer: Stopwatch::new(),
+            performance: 1.0,
+        }
+    }
+}
+
+impl WaveDirector {
+    pub fn is_boss_wave(wave: u32) -> bool {
+        wave > 0 && wave % BOSS_WAVE_INTERVAL == 0
+    }
+
+    pub fn wave_budget(&self, wave: u32) -> u32 {
+        let budget = WAVE_BASE_BUDGET * WAVE_BUDGET_GROWTH.powi(wave as i32 - 1) * self.performance;
+
+        budget.round() as u32
+    }
+
+    fn start_next_wave(&mut self) -> WaveStarted {
+        self.wave += 1;
+        self.phase = WavePhase::Spawning;
+        self.budget = self.wave_budget(self.wave);
+        self.boss_pending = Self::is_boss_wave(self.wave);
+        self.spawn_timer.reset();
+        self.wave_timer.reset();
+
+        WaveStarted {
+            wave: self.wave,
+            boss: self.boss_pending,
+        }
+    }
+
+    fn clear_wave(&mut self) -> WaveCleared {
+        let duration = self.wave_timer.elapsed_secs();
+
+        self.phase = WavePhase::Resting;
+        self.rest_timer = Timer::from_seconds(WAVE_REST_DURATION, TimerMode::Once);
+        self.performance = (WAVE_TARGET_DURATION / duration.max(1.0)).clamp(0.75, 1.5);
+
+        WaveCleared {
+            wave: self.wave,
+            duration,
+        }
+    }
+}
+
+#[derive(Event, Clone)]
+pub struct WaveStarted {
+    pub wave: u32,
+    pub boss: bool,
+}
+
+#[derive(Event, Clone)]
+pub struct WaveCleared {
+    pub wave: u32,
+    pub duration: f32,
+}
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WaveDirector>()
+            .add_event::<WaveStarted>()
+            .add_event::<WaveCleared>()
+            .add_systems(OnEnter(GameState::Bootstraping), reset_wave_director)
+            .add_systems(
+                FixedUpdate,
+                (update_wave_director, spawn_wave_enemies)
+                    .chain()
+                    .in_set(GameplaySet::Spawn)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn reset_wave_director(mut director: ResMut<WaveDirector>) {
+    *director = WaveDirector::default();
+}
+
+fn update_wave_director(
+    time: Res<Time>,
+    mut director: ResMut<WaveDirector>,
+    enemy_query: Query<(), With<Enemy>>,
+    mut wave_started_events: EventWriter<WaveStarted>,
+    mut wave_cleared_events: EventWriter<WaveCleared>,
+) {
+    match director.phase {
+        WavePhase::Resting => {
+            if director.rest_timer.tick(time.delta()).just_finished() {
+                let event = director.start_next_wave();
+                wave_started_events.send(event);
+            }
+        }
+        WavePhase::Spawning => {
+            director.wave_timer.tick(time.delta());
+        }
+        WavePhase::Fighting => {
+            director.wave_timer.tick(time.delta());
+
+            if enemy_query.is_empty() {
+                let event = director.clear_wave();
+                wave_cleared_events.send(event);
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_wave_enemies(
+    mut commands: Commands,
+    time: Res<Time>,
+    handle: Res<GlobalTextureAtlas>,
+    bounds: Res<WorldBounds>,
+    map: Res<TileMap>,
+    mut rng: ResMut<GameRng>,
+    mut director: ResMut<WaveDirector>,
+    player_query: Query<&Transform, With<Player>>,
+    enemy_query: Query<(), With<Enemy>>,
+) {
+    if director.phase != WavePhase::Spawning || player_query.is_empty() {
+        return;
+    }
+
+    if !director.spawn_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let player_position = player_query.single().translation.truncate();
+    let num_enemies = enemy_query.iter().len();
+    let mut capacity = MAX_NUM_ENEMIES.saturating_sub(num_enemies);
+    let rng = rng.stream(RngStream::Spawning);
+
+    if director.boss_pending && capacity > 0 {
+        if let Some(position) = free_spawn_position(rng, &bounds, &map, player_position) {
+            spawn_enemy(
+                &mut commands,
+                &handle,
+                EnemyType::Boss,
+                position.extend(1.0),
+            );
+
+            director.boss_pending = false;
+            capacity -= 1;
+        }
+    }
+
+    for _ in 0..capacity.min(ENEMY_SPAWN_BATCH_SIZE) {
+        let affordable = EnemyType::ALL
+            .into_iter()
+            .filter(|kind| {
+                let archetype = kind.archetype();
+                archetype.spawn_weight > 0 && archetype.spawn_cost <= director.budget
+            })
+            .collect::<Vec<_>>();
+
+        let Ok(spawn_table) =
+            WeightedIndex::new(affordable.iter().map(|kind| kind.archetype().spawn_weight))
+        else {
+            director.budget = 0;
+            break;
+        };
+
+        let kind = affordable[spawn_table.sample(rng)];
+
+        // Tried again on the next spawn tick, the budget is still there
+        let Some(position) = free_spawn_position(rng, &bounds, &map, player_position) else {
+            continue;
+        };
+
+        spawn_enemy(&mut commands, &handle, kind, position.extend(1.0));
+
+        director.budget -= kind.archetype().spawn_cost;
+    }
+
+    if director.budget == 0 && !director.boss_pending {
+        director.phase = WavePhase::Fighting;
+    }
+}
+
+/// Spawn position that isn't on a solid tile, `None` when every attempt landed on one.
+pub fn free_spawn_position(
+    rng: &mut impl Rng,
+    bounds: &WorldBounds,
+    map: &TileMap,
+    player_position: Vec2,
+) -> Option<Vec2> {
+    (0..ENEMY_SPAWN_ATTEMPTS).find_map(|_| {
+        let (x, y) =
+            get_random_spawn_position(rng, bounds, &map.enemy_spawn_zones, player_position);
+        let position = Vec2::new(x, y);
+        let blocked = map.tile_at(position).is_some_and(|tile| map.is_solid(tile));
+
+        (!blocked).then_some(position)
+    })
+}
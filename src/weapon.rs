@@ -1,151 +1,631 @@
-use std::{f32::consts::PI, time::Instant};
+use std::f32::consts::{PI, TAU};
 
 use bevy::{
+    asset::{io::Reader, ron, AssetLoader, AsyncReadExt, LoadContext},
     math::{vec2, vec3},
     prelude::*,
     time::Stopwatch,
 };
 use rand::Rng;
+use serde::Deserialize;
+use thiserror::Error;
 
-use crate::{player::Player, state::GameState, *};
+use crate::{
+    collision::{Collider, EnemySpatialIndex},
+    enemy::update_enemy_projectiles,
+    input::PlayerInput,
+    interpolation::InterpolatedTranslation,
+    player::{handle_player_death, Player},
+    rng::{GameRng, RngStream},
+    schedule::GameplaySet,
+    state::GameState,
+    stats::{Stat, Stats},
+    world::GameEntity,
+    *,
+};
 
 pub struct WeaponPlugin;
 
 #[derive(Component)]
 pub struct Weapon;
 
+/// Held weapon the player aims and fires, the other inventory slots are holstered.
 #[derive(Component)]
-pub struct WeaponTimer(pub Stopwatch);
+pub struct ActiveWeapon;
+
+/// Weapon circling the player, firing on its own at the nearest enemy in range.
+#[derive(Component, Default)]
+pub struct Orbiting {
+    pub target: Option<Vec2>,
+}
+
+/// Weapons carried by the player.
+#[derive(Component, Default)]
+pub struct Inventory {
+    /// Held weapons in slot order, switched between with the number keys or the mouse wheel.
+    pub weapons: Vec<Entity>,
+    /// Slot of the `ActiveWeapon`.
+    pub active: usize,
+    /// Orbiting weapons in the order they were gained, spaced evenly around the player.
+    pub orbiting: Vec<Entity>,
+    /// Angle of the first orbiting weapon, in radians.
+    pub orbit_angle: f32,
+}
+
+/// Slot picked with the number keys or the mouse wheel, passed on with the input of the next tick.
+#[derive(Resource, Default)]
+pub struct WeaponSelection(pub Option<u8>);
+
+/// Definition of the weapons gained through `Stat::OrbitingWeapons`, loaded with the run.
+#[derive(Resource, Default)]
+pub struct OrbitingWeapon(pub Handle<WeaponDefinition>);
 
 #[derive(Component)]
-pub struct Projectile;
+pub struct WeaponTimer(pub Stopwatch);
+
+/// Rounds fired from the current magazine, for weapons that have one.
+#[derive(Component, Default)]
+pub struct Ammo {
+    pub fired: u32,
+    pub reload: Option<Timer>,
+}
+
+impl Ammo {
+    pub fn remaining(&self, definition: &WeaponDefinition) -> Option<u32> {
+        definition
+            .magazine_size
+            .map(|size| size.saturating_sub(self.fired))
+    }
+
+    fn start_reload(&mut self, definition: &WeaponDefinition) {
+        if self.reload.is_none() {
+            self.reload = Some(Timer::from_seconds(definition.reload_time, TimerMode::Once));
+        }
+    }
+}
 
 #[derive(Component)]
-pub struct SpawnInstant(Instant);
+pub struct Projectile {
+    pub damage: f32,
+    pub speed: f32,
+    /// Despawns the projectile once finished, ticked with game time.
+    pub lifetime: Timer,
+    /// Number of additional enemies the projectile can pass through.
+    pub pierce: u32,
+    /// Enemies already damaged by this projectile.
+    pub hits: Vec<Entity>,
+}
+
+#[derive(Event, Clone)]
+pub struct ProjectileHitEvent {
+    pub projectile: Entity,
+    pub target: Entity,
+    pub damage: f32,
+    pub position: Vec2,
+}
+
+/// Designer-authored weapon stats, loaded from `assets/weapons/*.weapon.ron`.
+#[derive(Asset, TypePath, Debug, Clone, Deserialize)]
+pub struct WeaponDefinition {
+    pub name: String,
+    /// Shots per second.
+    pub fire_rate: f32,
+    pub pellets_per_shot: u32,
+    /// Maximum random offset applied to each component of the aim direction.
+    pub spread: f32,
+    /// Units per second.
+    pub projectile_speed: f32,
+    pub projectile_damage: f32,
+    /// Seconds before a projectile is despawned.
+    pub projectile_lifetime: f32,
+    /// Number of additional enemies each projectile can pass through.
+    #[serde(default)]
+    pub projectile_pierce: u32,
+    /// Shots before the weapon has to reload, unlimited when omitted.
+    #[serde(default)]
+    pub magazine_size: Option<u32>,
+    /// Seconds it takes to refill the magazine.
+    #[serde(default)]
+    pub reload_time: f32,
+    pub sprite_index: usize,
+    pub projectile_sprite_index: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum WeaponDefinitionError {
+    #[error("could not read weapon definition: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse weapon definition: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("{0} must be greater than zero")]
+    NotPositive(&'static str),
+    #[error("{0} must not be negative")]
+    Negative(&'static str),
+}
+
+impl WeaponDefinition {
+    pub fn parse(bytes: &[u8]) -> Result<Self, WeaponDefinitionError> {
+        let definition: Self = ron::de::from_bytes(bytes)?;
+        definition.validate()?;
+
+        Ok(definition)
+    }
+
+    /// Rejects values the firing code can't handle, like a negative spread range.
+    fn validate(&self) -> Result<(), WeaponDefinitionError> {
+        let positive = [
+            ("fire_rate", self.fire_rate),
+            ("projectile_speed", self.projectile_speed),
+            ("projectile_lifetime", self.projectile_lifetime),
+        ];
+        let non_negative = [
+            ("spread", self.spread),
+            ("projectile_damage", self.projectile_damage),
+            ("reload_time", self.reload_time),
+        ];
+
+        if let Some((field, _)) = positive
+            .into_iter()
+            .find(|(_, value)| value.is_nan() || *value <= 0.0)
+        {
+            return Err(WeaponDefinitionError::NotPositive(field));
+        }
+
+        if self.pellets_per_shot == 0 {
+            return Err(WeaponDefinitionError::NotPositive("pellets_per_shot"));
+        }
+
+        if self.magazine_size == Some(0) {
+            return Err(WeaponDefinitionError::NotPositive("magazine_size"));
+        }
+
+        if let Some((field, _)) = non_negative
+            .into_iter()
+            .find(|(_, value)| value.is_nan() || *value < 0.0)
+        {
+            return Err(WeaponDefinitionError::Negative(field));
+        }
+
+        Ok(())
+    }
+
+    /// Distance a projectile travels before it despawns.
+    pub fn range(&self) -> f32 {
+        self.projectile_speed * self.projectile_lifetime
+    }
+
+    /// Fire rate, damage and pellets as fired by a wielder with `stats`, the definition holds
+    /// the base values.
+    pub fn firing_stats(&self, stats: &Stats) -> FiringStats {
+        FiringStats {
+            fire_rate: stats.apply(Stat::FireRate, self.fire_rate),
+            damage: stats.apply(Stat::Damage, self.projectile_damage),
+            pellets: stats
+                .apply(Stat::Pellets, self.pellets_per_shot as f32)
+                .round()
+                .max(0.0) as u32,
+        }
+    }
+}
+
+/// The values of a `WeaponDefinition` scaled by the wielder's `Stats`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FiringStats {
+    /// Shots per second.
+    pub fire_rate: f32,
+    pub damage: f32,
+    pub pellets: u32,
+}
+
+#[derive(Default)]
+struct WeaponDefinitionLoader;
+
+impl AssetLoader for WeaponDefinitionLoader {
+    type Asset = WeaponDefinition;
+    type Settings = ();
+    type Error = WeaponDefinitionError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        WeaponDefinition::parse(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["weapon.ron"]
+    }
+}
 
 #[derive(Component)]
-struct ProjectileDirection(Vec3);
+pub struct ProjectileDirection(pub Vec3);
 
 impl Plugin for WeaponPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                update_weapon_transform,
-                handle_weapon_input,
-                update_projectile,
-                despawn_old_projectiles,
+        app.add_event::<ProjectileHitEvent>()
+            .init_asset::<WeaponDefinition>()
+            .init_asset_loader::<WeaponDefinitionLoader>()
+            .init_resource::<WeaponSelection>()
+            .init_resource::<OrbitingWeapon>()
+            .add_systems(OnEnter(GameState::Bootstraping), clear_weapon_selection)
+            .add_systems(
+                Update,
+                update_weapon_sprite.run_if(in_state(GameState::Playing)),
             )
-                .run_if(in_state(GameState::Playing)),
-        );
+            .add_systems(
+                FixedUpdate,
+                (
+                    // Aim at the enemies where they ended up this tick
+                    (
+                        switch_weapon,
+                        update_orbiting_weapons,
+                        update_weapon_transform,
+                        handle_weapon_input,
+                    )
+                        .chain()
+                        .in_set(GameplaySet::Damage)
+                        .after(handle_player_death),
+                    update_projectile
+                        .in_set(GameplaySet::Movement)
+                        .after(update_enemy_projectiles),
+                    despawn_old_projectiles.in_set(GameplaySet::Cleanup),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
     }
 }
 
-fn update_weapon_transform(
-    player_query: Query<&Transform, With<Player>>,
-    mut weapon_query: Query<&mut Transform, (With<Weapon>, Without<Player>)>,
-    cursor_position: Res<CursorPosition>,
+fn clear_weapon_selection(mut selection: ResMut<WeaponSelection>) {
+    selection.0 = None;
+}
+
+fn switch_weapon(
+    mut commands: Commands,
+    input: Res<PlayerInput>,
+    mut player_query: Query<&mut Inventory, With<Player>>,
+    mut weapon_query: Query<&mut Visibility, With<Weapon>>,
+) {
+    let (Some(slot), Ok(mut inventory)) = (input.weapon, player_query.get_single_mut()) else {
+        return;
+    };
+
+    let slot = slot as usize;
+
+    if slot >= inventory.weapons.len() || slot == inventory.active {
+        return;
+    }
+
+    let holstered = inventory.weapons[inventory.active];
+    let drawn = inventory.weapons[slot];
+
+    commands.entity(holstered).remove::<ActiveWeapon>();
+    commands.entity(drawn).insert(ActiveWeapon);
+
+    for (entity, visibility) in [
+        (holstered, Visibility::Hidden),
+        (drawn, Visibility::Inherited),
+    ] {
+        if let Ok(mut current) = weapon_query.get_mut(entity) {
+            *current = visibility;
+        }
+    }
+
+    inventory.active = slot;
+}
+
+/// Spawns or removes orbiting weapons until there are as many as the player's stats call for.
+fn update_orbiting_weapons(
+    mut commands: Commands,
+    handle: Res<GlobalTextureAtlas>,
+    orbiting_weapon: Res<OrbitingWeapon>,
+    mut player_query: Query<(&Transform, &Stats, &mut Inventory), With<Player>>,
 ) {
-    if player_query.is_empty() || weapon_query.is_empty() {
+    let Ok((transform, stats, mut inventory)) = player_query.get_single_mut() else {
         return;
+    };
+
+    let count = stats.get(Stat::OrbitingWeapons).round().max(0.0) as usize;
+    let position = transform.translation.truncate();
+
+    while inventory.orbiting.len() < count {
+        let weapon = spawn_weapon(&mut commands, &handle, orbiting_weapon.0.clone(), position);
+
+        commands.entity(weapon).insert(Orbiting::default());
+        inventory.orbiting.push(weapon);
     }
 
-    let player_position = player_query.single().translation.truncate();
-    let cursor_position = match cursor_position.0 {
-        Some(pos) => pos,
-        None => player_position,
+    while inventory.orbiting.len() > count {
+        if let Some(weapon) = inventory.orbiting.pop() {
+            commands.entity(weapon).despawn();
+        }
+    }
+}
+
+fn update_weapon_transform(
+    time: Res<Time>,
+    input: Res<PlayerInput>,
+    definitions: Res<Assets<WeaponDefinition>>,
+    enemy_index: Res<EnemySpatialIndex>,
+    mut player_query: Query<(&Transform, &mut Inventory), With<Player>>,
+    mut weapon_query: Query<
+        (
+            &mut Transform,
+            &Handle<WeaponDefinition>,
+            Option<&mut Orbiting>,
+        ),
+        (With<Weapon>, Without<Player>),
+    >,
+) {
+    let Ok((player_transform, mut inventory)) = player_query.get_single_mut() else {
+        return;
     };
-    let mut weapon_transform = weapon_query.single_mut();
 
-    let angle =
-        (player_position.y - cursor_position.y).atan2(player_position.x - cursor_position.x) + PI;
+    let player_position = player_transform.translation.truncate();
+
+    if let Some(active) = inventory.weapons.get(inventory.active) {
+        if let Ok((mut weapon_transform, _, _)) = weapon_query.get_mut(*active) {
+            let cursor_position = match input.aim {
+                Some(pos) => pos,
+                None => player_position,
+            };
+
+            let angle = (player_position.y - cursor_position.y)
+                .atan2(player_position.x - cursor_position.x)
+                + PI;
+
+            weapon_transform.rotation = Quat::from_rotation_z(angle);
+
+            let offset = 20.0;
+            let new_weapon_position = vec2(
+                player_position.x + offset * angle.cos() - 5.0,
+                player_position.y + offset * angle.sin() - 15.0,
+            );
+
+            weapon_transform.translation = vec3(new_weapon_position.x, new_weapon_position.y, 15.0);
+        }
+    }
+
+    inventory.orbit_angle =
+        (inventory.orbit_angle + WEAPON_ORBIT_SPEED * time.delta_seconds()) % TAU;
+
+    let spacing = TAU / inventory.orbiting.len().max(1) as f32;
+
+    for (index, weapon) in inventory.orbiting.iter().enumerate() {
+        let Ok((mut weapon_transform, definition, Some(mut orbiting))) =
+            weapon_query.get_mut(*weapon)
+        else {
+            continue;
+        };
 
-    weapon_transform.rotation = Quat::from_rotation_z(angle);
+        let angle = inventory.orbit_angle + spacing * index as f32;
+        let position = player_position + Vec2::from_angle(angle) * WEAPON_ORBIT_RADIUS;
+        let range = definitions
+            .get(definition)
+            .map_or(0.0, WeaponDefinition::range);
 
-    let offset = 20.0;
-    let new_weapon_position = vec2(
-        player_position.x + offset * angle.cos() - 5.0,
-        player_position.y + offset * angle.sin() - 15.0,
-    );
+        orbiting.target = enemy_index
+            .0
+            .within_radius(position, range)
+            .map(|(_, enemy_position)| enemy_position)
+            .min_by(|a, b| {
+                a.distance_squared(position)
+                    .total_cmp(&b.distance_squared(position))
+            });
 
-    weapon_transform.translation = vec3(new_weapon_position.x, new_weapon_position.y, 15.0);
+        // Point outwards while there is nothing to shoot at
+        let heading = orbiting
+            .target
+            .map_or(angle, |target| (target - position).to_angle());
+
+        weapon_transform.rotation = Quat::from_rotation_z(heading);
+        weapon_transform.translation = position.extend(15.0);
+    }
 }
 
+fn update_weapon_sprite(
+    definitions: Res<Assets<WeaponDefinition>>,
+    mut weapon_query: Query<(&mut TextureAtlas, &Handle<WeaponDefinition>), With<Weapon>>,
+) {
+    for (mut texture_atlas, definition) in weapon_query.iter_mut() {
+        if let Some(definition) = definitions.get(definition) {
+            if texture_atlas.index != definition.sprite_index {
+                texture_atlas.index = definition.sprite_index;
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn handle_weapon_input(
     mut commands: Commands,
     time: Res<Time>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    input: Res<PlayerInput>,
     handle: Res<GlobalTextureAtlas>,
-    mut weapon_query: Query<(&Transform, &mut WeaponTimer), With<Weapon>>,
+    mut rng: ResMut<GameRng>,
+    definitions: Res<Assets<WeaponDefinition>>,
+    player_query: Query<(&Stats, &Inventory), With<Player>>,
+    mut weapon_query: Query<
+        (
+            &Transform,
+            &mut WeaponTimer,
+            &mut Ammo,
+            &Handle<WeaponDefinition>,
+            Has<ActiveWeapon>,
+            Option<&Orbiting>,
+        ),
+        With<Weapon>,
+    >,
 ) {
-    if weapon_query.is_empty() {
+    let Ok((stats, inventory)) = player_query.get_single() else {
         return;
-    }
+    };
 
-    let (weapon_transform, mut weapon_timer) = weapon_query.single_mut();
-    let weapon_position = weapon_transform.translation.truncate();
+    // Slot order, so the weapons draw from the RNG stream in the same order every run
+    for entity in inventory.weapons.iter().chain(&inventory.orbiting) {
+        let Ok((weapon_transform, mut weapon_timer, mut ammo, definition, active, orbiting)) =
+            weapon_query.get_mut(*entity)
+        else {
+            continue;
+        };
 
-    weapon_timer.0.tick(time.delta());
+        let weapon_position = weapon_transform.translation.truncate();
 
-    if !mouse_button_input.pressed(MouseButton::Left) {
-        return;
-    }
+        weapon_timer.0.tick(time.delta());
 
-    if weapon_timer.0.elapsed_secs() >= PROJECTILE_SPAWN_INTERVAL {
-        weapon_timer.0.reset();
+        // The definition is loaded asynchronously, the weapon can't fire until it's ready
+        let Some(definition) = definitions.get(definition) else {
+            continue;
+        };
+        let firing = definition.firing_stats(stats);
 
-        let mut rng = rand::thread_rng();
-        let projectile_direction = weapon_transform.local_x();
+        if let Some(reload) = ammo.reload.as_mut() {
+            if !reload.tick(time.delta()).finished() {
+                continue;
+            }
 
-        for _ in 0..3 {
-            let direction = vec3(
-                projectile_direction.x + rng.gen_range(-0.5..0.5),
-                projectile_direction.y + rng.gen_range(-0.5..0.5),
-                projectile_direction.z,
-            );
+            *ammo = Ammo::default();
+        }
 
-            commands.spawn((
-                SpriteBundle {
-                    texture: handle.image.clone().unwrap(),
-                    transform: Transform::from_translation(vec3(
-                        weapon_position.x,
-                        weapon_position.y,
-                        1.0,
-                    ))
-                    .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
-                    ..default()
-                },
-                TextureAtlas {
-                    layout: handle.layout.clone().unwrap(),
-                    index: 16,
-                },
-                SpawnInstant(Instant::now()),
-                Projectile,
-                ProjectileDirection(direction),
-            ));
+        if active && input.reload && ammo.fired > 0 {
+            ammo.start_reload(definition);
+            continue;
+        }
+
+        // Orbiting weapons fire on their own, holstered ones not at all
+        let trigger = match orbiting {
+            Some(orbiting) => orbiting.target.is_some(),
+            None => active && input.fire,
+        };
+
+        if !trigger {
+            continue;
+        }
+
+        if weapon_timer.0.elapsed_secs() >= 1.0 / firing.fire_rate {
+            weapon_timer.0.reset();
+
+            ammo.fired += 1;
+
+            if ammo.remaining(definition) == Some(0) {
+                ammo.start_reload(definition);
+            }
+
+            let rng = rng.stream(RngStream::Weapons);
+            let projectile_direction = weapon_transform.local_x();
+            let spread = definition.spread;
+            let projectile_position = vec3(weapon_position.x, weapon_position.y, 1.0);
+
+            for _ in 0..firing.pellets {
+                let direction = vec3(
+                    projectile_direction.x + rng.gen_range(-spread..=spread),
+                    projectile_direction.y + rng.gen_range(-spread..=spread),
+                    projectile_direction.z,
+                );
+
+                spawn_projectile(
+                    &mut commands,
+                    &handle,
+                    definition,
+                    firing.damage,
+                    projectile_position,
+                    direction,
+                );
+            }
         }
     }
 }
 
+pub fn spawn_weapon(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    definition: Handle<WeaponDefinition>,
+    position: Vec2,
+) -> Entity {
+    let translation = position.extend(15.0);
+
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: handle.image.clone().unwrap_or_default(),
+                transform: Transform::from_translation(translation)
+                    .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+                ..default()
+            },
+            TextureAtlas {
+                layout: handle.layout.clone().unwrap_or_default(),
+                index: 17,
+            },
+            Weapon,
+            InterpolatedTranslation::new(translation),
+            WeaponTimer(Stopwatch::new()),
+            Ammo::default(),
+            definition,
+            GameEntity,
+        ))
+        .id()
+}
+
+pub fn spawn_projectile(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    definition: &WeaponDefinition,
+    damage: f32,
+    translation: Vec3,
+    direction: Vec3,
+) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: handle.image.clone().unwrap_or_default(),
+                transform: Transform::from_translation(translation)
+                    .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+                ..default()
+            },
+            TextureAtlas {
+                layout: handle.layout.clone().unwrap_or_default(),
+                index: definition.projectile_sprite_index,
+            },
+            Collider::sprite_circle(2.5),
+            InterpolatedTranslation::new(translation),
+            Projectile {
+                damage,
+                speed: definition.projectile_speed,
+                lifetime: Timer::from_seconds(definition.projectile_lifetime, TimerMode::Once),
+                pierce: definition.projectile_pierce,
+                hits: Vec::new(),
+            },
+            ProjectileDirection(direction),
+            GameEntity,
+        ))
+        .id()
+}
+
 fn update_projectile(
-    mut projectile_query: Query<(&mut Transform, &ProjectileDirection), With<Projectile>>,
+    time: Res<Time>,
+    mut projectile_query: Query<(&mut Transform, &Projectile, &ProjectileDirection)>,
 ) {
     if projectile_query.is_empty() {
         return;
     }
 
-    for (mut t, dir) in projectile_query.iter_mut() {
-        t.translation += dir.0.normalize() * Vec3::splat(PROJECTILE_SPEED);
+    for (mut t, projectile, dir) in projectile_query.iter_mut() {
+        t.translation += dir.0.normalize() * projectile.speed * time.delta_seconds();
         t.translation.z = 10.0;
     }
 }
 
-fn despawn_old_projectiles(
+pub fn despawn_old_projectiles(
     mut commands: Commands,
-    projectile_query: Query<(&SpawnInstant, Entity), With<Projectile>>,
+    time: Res<Time>,
+    mut projectile_query: Query<(&mut Projectile, Entity)>,
 ) {
-    for (instant, entity) in projectile_query.iter() {
-        if instant.0.elapsed().as_secs_f32() > 1.0 {
+    for (mut projectile, entity) in projectile_query.iter_mut() {
+        if projectile.lifetime.tick(time.delta()).finished() {
             commands.entity(entity).despawn();
         }
     }
@@ -1,87 +1,166 @@
-use bevy::{math::vec3, prelude::*, time::Stopwatch};
-use rand::Rng;
+use bevy::{asset::LoadState, math::vec2, prelude::*};
 
 use crate::{
     animation::AnimationTimer,
+    collision::Collider,
     constants::*,
+    interpolation::InterpolatedTranslation,
     player::{Health, Player, PlayerState},
     state::GameState,
-    weapon::{Weapon, WeaponTimer},
+    stats::{Stat, Stats},
+    tilemap::{generate_tilemap, TileMap},
+    weapon::{spawn_weapon, ActiveWeapon, Inventory, OrbitingWeapon, Weapon, WeaponDefinition},
     GlobalTextureAtlas,
 };
 
 #[derive(Component)]
 pub struct GameEntity;
 
+/// Playable area, the player and enemies can't leave it.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct WorldBounds(pub Rect);
+
+impl Default for WorldBounds {
+    fn default() -> Self {
+        Self(Rect::new(-WORLD_W, -WORLD_H, WORLD_W, WORLD_H))
+    }
+}
+
+impl WorldBounds {
+    /// Closest position keeping a box of `half_extents` inside the bounds.
+    pub fn clamp(&self, position: Vec2, half_extents: Vec2) -> Vec2 {
+        position.clamp(self.0.min + half_extents, self.0.max - half_extents)
+    }
+}
+
 pub struct WorldPlugin;
 
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            OnEnter(GameState::Bootstraping),
-            (init_world, decorate_world),
-        )
-        .add_systems(OnExit(GameState::Playing), despawn_game_entities);
+        app.init_resource::<WorldBounds>()
+            .add_systems(
+                OnEnter(GameState::Bootstraping),
+                (init_world, spawn_world_border).after(generate_tilemap),
+            )
+            .add_systems(
+                Update,
+                start_when_weapon_loaded.run_if(in_state(GameState::Bootstraping)),
+            )
+            .add_systems(OnExit(GameState::Playing), despawn_game_entities);
     }
 }
 
 fn init_world(
     mut commands: Commands,
     handle: Res<GlobalTextureAtlas>,
-    mut next_state: ResMut<NextState<GameState>>,
+    asset_server: Res<AssetServer>,
+    map: Res<TileMap>,
+    mut orbiting_weapon: ResMut<OrbitingWeapon>,
 ) {
+    let spawn = map.player_spawn.extend(0.0);
+
+    let mut inventory = Inventory::default();
+
+    for (slot, path) in PLAYER_STARTING_WEAPONS.into_iter().enumerate() {
+        let definition = asset_server.load::<WeaponDefinition>(path);
+        let weapon = spawn_weapon(&mut commands, &handle, definition, spawn.truncate());
+
+        // Only the first slot is drawn
+        if slot == 0 {
+            commands.entity(weapon).insert(ActiveWeapon);
+        } else {
+            commands.entity(weapon).insert(Visibility::Hidden);
+        }
+
+        inventory.weapons.push(weapon);
+    }
+
+    orbiting_weapon.0 = asset_server.load(ORBITING_WEAPON);
+
     commands.spawn((
         SpriteBundle {
-            texture: handle.image.clone().unwrap(),
-            transform: Transform::from_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+            texture: handle.image.clone().unwrap_or_default(),
+            transform: Transform::from_translation(spawn)
+                .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
             ..default()
         },
         TextureAtlas {
-            layout: handle.layout.clone().unwrap(),
+            layout: handle.layout.clone().unwrap_or_default(),
             index: 0,
         },
         Player,
+        Collider::sprite_aabb(10.0, 14.0),
+        InterpolatedTranslation::new(spawn),
         PlayerState::default(),
         AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
         Health(PLAYER_HEALTH),
+        Stats::default()
+            .with_base(Stat::MaxHealth, PLAYER_HEALTH)
+            .with_base(Stat::Speed, PLAYER_SPEED),
+        inventory,
         GameEntity,
     ));
-    commands.spawn((
-        SpriteBundle {
-            texture: handle.image.clone().unwrap(),
-            transform: Transform::from_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
-            ..default()
-        },
-        TextureAtlas {
-            layout: handle.layout.clone().unwrap(),
-            index: 17,
-        },
-        Weapon,
-        WeaponTimer(Stopwatch::new()),
-        GameEntity,
-    ));
+}
 
-    next_state.set(GameState::Playing);
+// Starting before the weapon can fire would shift every run by a few ticks and break replays
+fn start_when_weapon_loaded(
+    asset_server: Res<AssetServer>,
+    orbiting_weapon: Res<OrbitingWeapon>,
+    weapon_query: Query<&Handle<WeaponDefinition>, With<Weapon>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    // Orbiting weapons can be gained mid-run, their definition has to be ready as well
+    let definitions = weapon_query.iter().chain([&orbiting_weapon.0]);
+    let mut loaded = true;
+
+    for definition in definitions {
+        match asset_server.load_state(definition) {
+            LoadState::Loaded => {}
+            // A run without the weapon would play out differently than intended
+            LoadState::Failed(err) => {
+                error!("Could not load weapon definition: {err}");
+                exit.send(AppExit::error());
+                return;
+            }
+            _ => loaded = false,
+        }
+    }
+
+    if loaded {
+        next_state.set(GameState::Playing);
+    }
 }
 
-fn decorate_world(mut commands: Commands, handle: Res<GlobalTextureAtlas>) {
-    let mut rng = rand::thread_rng();
+fn spawn_world_border(mut commands: Commands, bounds: Res<WorldBounds>) {
+    let color = Color::srgb_u8(
+        WORLD_BORDER_COLOR.0,
+        WORLD_BORDER_COLOR.1,
+        WORLD_BORDER_COLOR.2,
+    );
+    let outer = bounds.0.inflate(WORLD_BORDER_THICKNESS);
+    let horizontal = vec2(outer.width(), WORLD_BORDER_THICKNESS);
+    let vertical = vec2(WORLD_BORDER_THICKNESS, outer.height());
+    let offset = WORLD_BORDER_THICKNESS / 2.0;
 
-    for _ in 0..NUM_WORLD_DECORATIONS {
-        let x = rng.gen_range(-WORLD_W..WORLD_W);
-        let y = rng.gen_range(-WORLD_H..WORLD_H);
+    let walls = [
+        (vec2(outer.center().x, bounds.0.max.y + offset), horizontal),
+        (vec2(outer.center().x, bounds.0.min.y - offset), horizontal),
+        (vec2(bounds.0.min.x - offset, outer.center().y), vertical),
+        (vec2(bounds.0.max.x + offset, outer.center().y), vertical),
+    ];
 
+    for (position, size) in walls {
         commands.spawn((
             SpriteBundle {
-                texture: handle.image.clone().unwrap(),
-                transform: Transform::from_translation(vec3(x, y, 0.0))
-                    .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_translation(position.extend(5.0)),
                 ..default()
             },
-            TextureAtlas {
-                layout: handle.layout.clone().unwrap(),
-                index: rng.gen_range(24..=25),
-            },
             GameEntity,
         ));
     }
@@ -0,0 +1,95 @@
+use bevy::{math::vec2, prelude::*};
+use shooter2d::{
+    enemy::EnemyType,
+    player::{Invulnerable, PlayerEnemyCollisionEvent},
+    testing::{weapon_definition, TestApp},
+    weapon::{ProjectileHitEvent, WeaponDefinition},
+};
+
+fn weapon(damage: f32, pierce: u32) -> WeaponDefinition {
+    WeaponDefinition {
+        projectile_damage: damage,
+        projectile_pierce: pierce,
+        ..weapon_definition()
+    }
+}
+
+#[test]
+fn projectile_damages_enemy_and_despawns() {
+    let mut app = TestApp::new();
+    app.record_events::<ProjectileHitEvent>();
+
+    let enemy = app.spawn_enemy(EnemyType::Tank, vec2(300.0, 0.0));
+    let projectile = app.spawn_projectile(&weapon(50.0, 0), vec2(250.0, 0.0), Vec2::X);
+
+    app.advance(10);
+
+    assert_eq!(app.enemy_health(enemy), Some(550.0));
+    assert!(!app.exists(projectile));
+
+    let hits = app.events::<ProjectileHitEvent>();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].target, enemy);
+    assert_eq!(hits[0].damage, 50.0);
+}
+
+#[test]
+fn piercing_projectile_hits_each_enemy_once() {
+    let mut app = TestApp::new();
+    app.record_events::<ProjectileHitEvent>();
+
+    let near = app.spawn_enemy(EnemyType::Tank, vec2(300.0, 0.0));
+    let far = app.spawn_enemy(EnemyType::Tank, vec2(380.0, 0.0));
+    let projectile = app.spawn_projectile(&weapon(50.0, 1), vec2(250.0, 0.0), Vec2::X);
+
+    app.advance(20);
+
+    assert_eq!(app.enemy_health(near), Some(550.0));
+    assert_eq!(app.enemy_health(far), Some(550.0));
+    assert!(!app.exists(projectile));
+
+    let targets = app
+        .events::<ProjectileHitEvent>()
+        .iter()
+        .map(|hit| hit.target)
+        .collect::<Vec<_>>();
+    assert_eq!(targets, [near, far]);
+}
+
+#[test]
+fn projectile_misses_enemy_off_its_path() {
+    let mut app = TestApp::new();
+
+    let enemy = app.spawn_enemy(EnemyType::Tank, vec2(300.0, 200.0));
+    let projectile = app.spawn_projectile(&weapon(50.0, 0), vec2(250.0, 0.0), Vec2::X);
+
+    app.advance(30);
+
+    assert_eq!(app.enemy_health(enemy), Some(600.0));
+    assert!(app.exists(projectile));
+}
+
+#[test]
+fn enemy_contact_damages_player_once_per_invulnerability_window() {
+    let mut app = TestApp::new();
+    app.record_events::<PlayerEnemyCollisionEvent>();
+
+    app.spawn_enemy(EnemyType::Grunt, vec2(10.0, 0.0));
+    app.advance(5);
+
+    let player = app.player();
+
+    assert!(!app.events::<PlayerEnemyCollisionEvent>().is_empty());
+    assert_eq!(app.player_health(), 90.0);
+    assert!(app.world().get::<Invulnerable>(player).is_some());
+}
+
+#[test]
+fn enemy_contact_knocks_player_back() {
+    let mut app = TestApp::new();
+
+    app.spawn_enemy(EnemyType::Grunt, vec2(10.0, 0.0));
+    app.advance(5);
+
+    assert!(app.player_position().x < 0.0);
+}
@@ -0,0 +1,101 @@
+use bevy::{math::vec2, prelude::*};
+use shooter2d::{
+    enemy::{EnemyKilledEvent, EnemyType},
+    player::{Invulnerable, Knockback, PlayerEnemyCollisionEvent},
+    state::GameState,
+    testing::{weapon_definition, TestApp},
+    weapon::WeaponDefinition,
+};
+
+fn weapon(damage: f32) -> WeaponDefinition {
+    WeaponDefinition {
+        projectile_damage: damage,
+        ..weapon_definition()
+    }
+}
+
+#[test]
+fn enemy_dies_when_health_runs_out() {
+    let mut app = TestApp::new();
+    app.record_events::<EnemyKilledEvent>();
+
+    let enemy = app.spawn_enemy(EnemyType::Runner, vec2(300.0, 0.0));
+    app.spawn_projectile(&weapon(50.0), vec2(250.0, 0.0), Vec2::X);
+
+    app.advance(10);
+
+    assert!(!app.exists(enemy));
+
+    let killed = app.events::<EnemyKilledEvent>();
+    assert_eq!(killed.len(), 1);
+    assert_eq!(killed[0].entity, enemy);
+    assert_eq!(killed[0].kind, EnemyType::Runner);
+}
+
+#[test]
+fn enemy_survives_non_lethal_damage() {
+    let mut app = TestApp::new();
+    app.record_events::<EnemyKilledEvent>();
+
+    let enemy = app.spawn_enemy(EnemyType::Grunt, vec2(300.0, 0.0));
+    app.spawn_projectile(&weapon(40.0), vec2(250.0, 0.0), Vec2::X);
+
+    app.advance(10);
+
+    assert_eq!(app.enemy_health(enemy), Some(60.0));
+    assert!(app.events::<EnemyKilledEvent>().is_empty());
+}
+
+#[test]
+fn exploder_blasts_player_and_despawns() {
+    let mut app = TestApp::new();
+
+    let exploder = app.spawn_enemy(EnemyType::Exploder, vec2(50.0, 0.0));
+    app.advance(3);
+
+    assert!(!app.exists(exploder));
+    assert_eq!(app.player_health(), 75.0);
+}
+
+#[test]
+fn invulnerable_player_takes_no_damage() {
+    let mut app = TestApp::new();
+
+    let player = app.player();
+    app.world_mut()
+        .entity_mut(player)
+        .insert(Invulnerable(Timer::from_seconds(10.0, TimerMode::Once)));
+
+    app.spawn_enemy(EnemyType::Grunt, vec2(10.0, 0.0));
+    app.advance(10);
+
+    assert_eq!(app.player_health(), 100.0);
+}
+
+#[test]
+fn harmless_hit_grants_no_invulnerability_or_knockback() {
+    let mut app = TestApp::new();
+
+    let player = app.player();
+    app.world_mut().send_event(PlayerEnemyCollisionEvent {
+        attacker: Entity::PLACEHOLDER,
+        attacker_position: vec2(10.0, 0.0),
+        damage: 0.0,
+    });
+    app.advance(1);
+
+    assert_eq!(app.player_health(), 100.0);
+    assert!(app.world().get::<Invulnerable>(player).is_none());
+    assert!(app.world().get::<Knockback>(player).is_none());
+}
+
+#[test]
+fn player_death_ends_the_run() {
+    let mut app = TestApp::new();
+
+    app.set_player_health(5.0);
+    app.spawn_enemy(EnemyType::Grunt, vec2(10.0, 0.0));
+    app.advance(5);
+
+    assert_eq!(app.state(), GameState::GameOver);
+}
@@ -0,0 +1,152 @@
+use bevy::{math::vec2, prelude::*};
+use shooter2d::{
+    constants::*,
+    enemy::{Enemy, EnemyType},
+    experience::{Experience, Upgrade, UpgradeChoices, XpGem},
+    input::PlayerInput,
+    replay::Replay,
+    stats::{Stat, Stats},
+    testing::{weapon_definition, TestApp},
+    weapon::WeaponDefinition,
+};
+
+fn gems(app: &mut TestApp) -> Vec<(Entity, u32)> {
+    app.world_mut()
+        .query::<(Entity, &XpGem)>()
+        .iter(app.world())
+        .map(|(entity, gem)| (entity, gem.0))
+        .collect()
+}
+
+#[test]
+fn dead_enemies_drop_xp_gems() {
+    let mut app = TestApp::new();
+    let enemy = app.spawn_enemy(EnemyType::Tank, vec2(500.0, 0.0));
+
+    app.world_mut().get_mut::<Enemy>(enemy).unwrap().health = 0.0;
+    app.tick();
+
+    let gems = gems(&mut app);
+
+    assert_eq!(gems.len(), 1);
+    assert_eq!(gems[0].1, EnemyType::Tank.archetype().xp);
+    assert!(app.position(gems[0].0).distance(vec2(500.0, 0.0)) < 1.0);
+}
+
+#[test]
+fn gems_in_the_magnet_radius_are_collected() {
+    let mut app = TestApp::new();
+    let near = app.spawn_xp_gem(2, vec2(XP_MAGNET_RADIUS - 10.0, 0.0));
+    let far = app.spawn_xp_gem(2, vec2(XP_MAGNET_RADIUS + 50.0, 0.0));
+
+    app.advance(60);
+
+    assert!(!app.exists(near));
+    assert!(app.exists(far));
+    assert_eq!(app.world().resource::<Experience>().xp, 2);
+}
+
+#[test]
+fn collecting_enough_xp_offers_upgrades() {
+    let mut app = TestApp::new();
+    let needed = app.world().resource::<Experience>().xp_to_next_level();
+
+    app.spawn_xp_gem(needed, Vec2::ZERO);
+    app.tick();
+
+    let experience = app.world().resource::<Experience>();
+    let choices = &app.world().resource::<UpgradeChoices>().0;
+
+    assert_eq!((experience.level, experience.xp), (2, 0));
+    assert_eq!(choices.len(), UPGRADE_CHOICES);
+    assert!(choices
+        .iter()
+        .all(|upgrade| choices.iter().filter(|other| *other == upgrade).count() == 1));
+}
+
+#[test]
+fn picking_an_upgrade_applies_it() {
+    let mut app = TestApp::new();
+
+    app.world_mut()
+        .resource_mut::<Experience>()
+        .pending_level_ups = 1;
+    app.world_mut().resource_mut::<UpgradeChoices>().0 =
+        vec![Upgrade::Speed, Upgrade::MaxHealth, Upgrade::Damage];
+    app.set_input(PlayerInput {
+        upgrade: Some(1),
+        ..default()
+    });
+    app.tick();
+
+    let player = app.player();
+
+    assert_eq!(
+        app.world()
+            .get::<Stats>(player)
+            .unwrap()
+            .get(Stat::MaxHealth),
+        PLAYER_HEALTH + UPGRADE_MAX_HEALTH_BONUS
+    );
+    assert_eq!(
+        app.player_health(),
+        PLAYER_HEALTH + UPGRADE_MAX_HEALTH_BONUS
+    );
+    assert_eq!(app.world().resource::<Experience>().pending_level_ups, 0);
+    assert!(app.world().resource::<UpgradeChoices>().0.is_empty());
+}
+
+#[test]
+fn several_levels_at_once_are_offered_one_after_another() {
+    let mut experience = Experience::default();
+    let needed = experience.xp_to_next_level();
+
+    assert_eq!(experience.add(needed * 4), experience.level - 1);
+    assert!(experience.pending_level_ups >= 2);
+    assert!(experience.xp < experience.xp_to_next_level());
+}
+
+#[test]
+fn upgrades_change_weapon_stats() {
+    let definition = WeaponDefinition {
+        fire_rate: 2.0,
+        ..weapon_definition()
+    };
+    let mut stats = Stats::default();
+    let upgrades = [
+        Upgrade::Damage,
+        Upgrade::Damage,
+        Upgrade::FireRate,
+        Upgrade::ExtraPellets,
+        Upgrade::ExtraPellets,
+        Upgrade::ExtraPellets,
+    ];
+
+    for upgrade in upgrades {
+        stats.add_modifier(upgrade.modifier());
+    }
+
+    let firing = definition.firing_stats(&stats);
+
+    assert_eq!(firing.damage, 10.0 * (1.0 + UPGRADE_DAMAGE_BONUS).powi(2));
+    assert_eq!(firing.fire_rate, 2.0 * (1.0 + UPGRADE_FIRE_RATE_BONUS));
+    assert_eq!(firing.pellets, 4);
+}
+
+#[test]
+fn replays_keep_upgrade_choices() {
+    let replay = Replay {
+        seed: 3,
+        map: None,
+        inputs: vec![
+            PlayerInput::default(),
+            PlayerInput {
+                upgrade: Some(2),
+                ..default()
+            },
+            PlayerInput::default(),
+        ],
+    };
+
+    assert_eq!(Replay::decode(&replay.encode()).unwrap(), replay);
+}
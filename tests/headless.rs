@@ -0,0 +1,16 @@
+use std::process::Command;
+
+#[test]
+fn headless_run_fails_without_weapon_definitions() {
+    let output = Command::new(env!("CARGO_BIN_EXE_shooter2d"))
+        .args(["--headless", "60"])
+        .env(
+            "BEVY_ASSET_ROOT",
+            std::env::temp_dir().join("shooter2d-no-assets"),
+        )
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).is_empty());
+}
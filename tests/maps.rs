@@ -0,0 +1,188 @@
+use bevy::{math::vec2, prelude::*};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use shooter2d::{
+    constants::*,
+    input::PlayerInput,
+    map::{MapDefinition, MapError},
+    replay::Replay,
+    state::GameState,
+    testing::TestApp,
+    tilemap::TileMap,
+    wave::free_spawn_position,
+    world::WorldBounds,
+};
+
+const ARENA: &str = r#"(
+    name: "Test arena",
+    legend: { ',': 24, '#': 57 },
+    decorations: [
+        ",.......",
+        "........",
+        "........",
+        "........",
+        ".......,",
+    ],
+    solid: [
+        "........",
+        "........",
+        "...#....",
+        "........",
+        "........",
+    ],
+    player_spawn: (1, 2),
+    enemy_spawn_zones: [(min: (6, 0), max: (7, 1))],
+    pickups: [(4, 4)],
+)"#;
+
+fn arena() -> MapDefinition {
+    MapDefinition::parse(ARENA.as_bytes()).unwrap()
+}
+
+#[test]
+fn shipped_maps_are_valid() {
+    for path in MAP_PATHS {
+        if let Err(err) = MapDefinition::load(format!("assets/{path}")) {
+            panic!("{path}: {err}");
+        }
+    }
+}
+
+#[test]
+fn layers_must_match_in_size() {
+    let ragged = ARENA.replace("\"...#....\"", "\"...#...\"");
+
+    assert!(matches!(
+        MapDefinition::parse(ragged.as_bytes()),
+        Err(MapError::LayerSize { layer: "solid", .. })
+    ));
+}
+
+#[test]
+fn tiles_must_be_in_the_legend() {
+    let unknown = ARENA.replace("\"...#....\"", "\"...#..x.\"");
+
+    assert!(matches!(
+        MapDefinition::parse(unknown.as_bytes()),
+        Err(MapError::UnknownTile('x'))
+    ));
+}
+
+#[test]
+fn player_spawn_must_be_free() {
+    let blocked = ARENA.replace("player_spawn: (1, 2)", "player_spawn: (3, 2)");
+
+    assert!(matches!(
+        MapDefinition::parse(blocked.as_bytes()),
+        Err(MapError::SpawnBlocked)
+    ));
+
+    let outside = ARENA.replace("player_spawn: (1, 2)", "player_spawn: (8, 2)");
+
+    assert!(matches!(
+        MapDefinition::parse(outside.as_bytes()),
+        Err(MapError::OutOfBounds("player spawn"))
+    ));
+}
+
+#[test]
+fn rows_are_read_from_the_top() {
+    let map = TileMap::from_definition(&arena());
+
+    assert_eq!((map.width, map.height), (8, 5));
+    assert_eq!(map.decorations[map.index(UVec2::new(0, 4))], Some(24));
+    assert_eq!(map.decorations[map.index(UVec2::new(7, 0))], Some(24));
+    assert!(map.is_solid(UVec2::new(3, 2)));
+    assert_eq!(map.solid.iter().flatten().count(), 1);
+    assert_eq!(
+        map.pickup_locations,
+        vec![map.tile_center(UVec2::new(4, 0))]
+    );
+}
+
+#[test]
+fn player_starts_at_the_map_spawn() {
+    let mut app = TestApp::with_map(arena());
+    let map = app.world().resource::<TileMap>().clone();
+
+    assert_eq!(app.world().resource::<WorldBounds>().0, map.bounds());
+    assert_eq!(app.player_position(), map.tile_center(UVec2::new(1, 2)));
+}
+
+#[test]
+fn enemies_spawn_in_the_spawn_zones() {
+    let mut app = TestApp::with_map(arena());
+    let zone = app.world().resource::<TileMap>().enemy_spawn_zones[0];
+
+    // The first wave starts after a short delay
+    for _ in 0..600 {
+        if !app.enemies().is_empty() {
+            break;
+        }
+
+        app.tick();
+    }
+
+    assert!(!app.enemies().is_empty());
+
+    let half_tile = vec2(WORLD_TILE_SIZE, WORLD_TILE_SIZE) / 2.0;
+
+    for enemy in app.enemies() {
+        assert!(zone.inflate(half_tile.x).contains(app.position(enemy)));
+    }
+}
+
+#[test]
+fn replays_play_back_on_the_recorded_map() {
+    let path = std::env::temp_dir().join("shooter2d-map-replay.replay");
+    let mut app = TestApp::recording(arena(), path.clone());
+
+    // Walks into the solid tile right of the spawn, then waits
+    app.set_input(PlayerInput {
+        movement: Vec2::X,
+        ..default()
+    });
+    app.advance(60);
+    app.set_input(PlayerInput::default());
+    app.advance(30);
+
+    let recorded_position = app.player_position();
+
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::GameOver);
+    app.tick();
+
+    let replay = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(replay.map, Some(arena()));
+
+    let mut playback = TestApp::playing_back(replay);
+
+    assert_eq!(
+        playback.world().resource::<TileMap>().solid,
+        TileMap::from_definition(&arena()).solid
+    );
+
+    playback.advance(80);
+
+    assert_eq!(playback.player_position(), recorded_position);
+}
+
+#[test]
+fn enemies_never_spawn_on_solid_tiles() {
+    let mut definition = arena();
+    definition.solid[0] = "......#.".to_string();
+
+    let map = TileMap::from_definition(&definition);
+    let bounds = WorldBounds(map.bounds());
+    let mut rng = ChaCha8Rng::seed_from_u64(4);
+
+    for _ in 0..500 {
+        let position = free_spawn_position(&mut rng, &bounds, &map, Vec2::ZERO).unwrap();
+        let tile = map.tile_at(position).unwrap();
+
+        assert!(!map.is_solid(tile), "enemy spawned on {tile}");
+    }
+}
@@ -0,0 +1,119 @@
+use bevy::{math::vec2, prelude::*};
+use shooter2d::{
+    constants::*, enemy::EnemyType, navigation::FlowField, testing::TestApp, tilemap::TileMap,
+};
+
+/// 9x9 map split by a wall in the middle column, open at the top row.
+fn walled_map() -> TileMap {
+    let mut map = TileMap::new(9, 9);
+
+    for y in 0..8 {
+        let index = map.index(UVec2::new(4, y));
+        map.solid[index] = Some(SOLID_TILES[0]);
+    }
+
+    map
+}
+
+fn searched(map: &TileMap, target: UVec2) -> FlowField {
+    let mut field = FlowField::new(map);
+
+    field.set_target(target);
+    assert!(field.step(usize::MAX));
+
+    field
+}
+
+#[test]
+fn paths_lead_around_walls() {
+    let map = walled_map();
+    let field = searched(&map, UVec2::new(2, 0));
+
+    // Straight left is blocked, the way round is through the gap at the top
+    let direction = field.direction(map.tile_center(UVec2::new(5, 0))).unwrap();
+
+    assert!(direction.y > 0.9);
+    assert!(field.distance(UVec2::new(6, 0)) > field.distance(UVec2::new(2, 8)));
+    assert_eq!(field.distance(UVec2::new(4, 0)), None);
+}
+
+#[test]
+fn enclosed_tiles_have_no_path() {
+    let mut map = walled_map();
+    let index = map.index(UVec2::new(4, 8));
+    map.solid[index] = Some(SOLID_TILES[0]);
+
+    let field = searched(&map, UVec2::new(2, 0));
+
+    assert_eq!(field.distance(UVec2::new(6, 0)), None);
+    assert_eq!(field.direction(map.tile_center(UVec2::new(6, 0))), None);
+}
+
+#[test]
+fn searches_are_spread_over_several_steps() {
+    let map = walled_map();
+    let mut field = searched(&map, UVec2::new(2, 0));
+
+    field.set_target(UVec2::new(6, 0));
+
+    // The previous field stays in use until the new search is done
+    assert!(!field.step(4));
+    assert_eq!(field.target(), Some(UVec2::new(2, 0)));
+
+    while !field.step(4) {}
+
+    assert_eq!(field.target(), Some(UVec2::new(6, 0)));
+    assert_eq!(field.distance(UVec2::new(6, 0)), Some(0));
+}
+
+#[test]
+fn enemies_walk_around_a_wall_to_the_player() {
+    let mut app = TestApp::new();
+
+    for y in -3..=3 {
+        app.spawn_solid_tile(vec2(4.0, y as f32) * WORLD_TILE_SIZE);
+    }
+
+    let enemy = app.spawn_enemy(EnemyType::Runner, vec2(8.0 * WORLD_TILE_SIZE, 0.0));
+    let mut closest = f32::MAX;
+
+    for _ in 0..600 {
+        app.tick();
+        closest = closest.min(app.position(enemy).distance(app.player_position()));
+    }
+
+    assert!(closest < 2.0 * WORLD_TILE_SIZE);
+}
+
+#[test]
+fn field_keeps_up_with_a_moving_player() {
+    let mut app = TestApp::new();
+    let start = app.player_position();
+
+    // One tile every few ticks, faster than a full search of the map takes
+    for step in 1..=40 {
+        app.set_player_position(start + vec2(step as f32 * WORLD_TILE_SIZE, 0.0));
+        app.advance(3);
+
+        let position = app.player_position();
+        let field = app.world().resource::<FlowField>();
+        let player = field.tile_at(position).unwrap();
+        let target = field.target().unwrap();
+
+        assert!(
+            player.x - target.x <= 2,
+            "field leads to {target} with the player in {player}"
+        );
+    }
+
+    app.advance(10);
+
+    // Repairing the previous field ends up where a fresh search does
+    let field = app.world().resource::<FlowField>();
+    let fresh = searched(app.world().resource::<TileMap>(), field.target().unwrap());
+    let size = field.size();
+
+    for tile in (0..size.y).flat_map(|y| (0..size.x).map(move |x| UVec2::new(x, y))) {
+        assert_eq!(field.distance(tile), fresh.distance(tile));
+    }
+}
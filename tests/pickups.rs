@@ -0,0 +1,217 @@
+use bevy::{math::vec2, prelude::*, utils::HashMap};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use shooter2d::{
+    constants::*,
+    enemy::{Enemy, EnemyType},
+    experience::XpGem,
+    map::MapDefinition,
+    pickup::{DropTable, DropTables, Pickup, PickupKind},
+    stats::{Stat, Stats},
+    testing::TestApp,
+    tilemap::TileMap,
+};
+
+fn pickups(app: &mut TestApp) -> Vec<(Entity, PickupKind)> {
+    app.world_mut()
+        .query::<(Entity, &Pickup)>()
+        .iter(app.world())
+        .map(|(entity, pickup)| (entity, pickup.0))
+        .collect()
+}
+
+#[test]
+fn heal_restores_health_up_to_the_maximum() {
+    let mut app = TestApp::new();
+
+    app.set_player_health(50.0);
+    let pickup = app.spawn_pickup(PickupKind::Heal, vec2(10.0, 0.0));
+    app.advance(2);
+
+    assert!(!app.exists(pickup));
+    assert_eq!(app.player_health(), 50.0 + PICKUP_HEAL_AMOUNT);
+
+    app.set_player_health(PLAYER_HEALTH - 5.0);
+    app.spawn_pickup(PickupKind::Heal, vec2(-10.0, 0.0));
+    app.advance(2);
+
+    assert_eq!(app.player_health(), PLAYER_HEALTH);
+}
+
+#[test]
+fn pickups_out_of_reach_stay_put() {
+    let mut app = TestApp::new();
+    let pickup = app.spawn_pickup(PickupKind::Heal, vec2(200.0, 0.0));
+
+    app.advance(10);
+
+    assert!(app.exists(pickup));
+}
+
+#[test]
+fn fire_rate_boost_wears_off() {
+    let mut app = TestApp::new();
+
+    app.spawn_pickup(PickupKind::FireRateBoost, Vec2::ZERO);
+    app.advance(2);
+
+    let player = app.player();
+    let fire_rate = |app: &TestApp| {
+        app.world()
+            .get::<Stats>(player)
+            .unwrap()
+            .apply(Stat::FireRate, 1.0)
+    };
+
+    assert_eq!(fire_rate(&app), PICKUP_FIRE_RATE_BOOST);
+
+    app.advance((PICKUP_FIRE_RATE_BOOST_DURATION * 60.0) as u32);
+
+    assert_eq!(fire_rate(&app), 1.0);
+}
+
+#[test]
+fn fire_rate_boosts_restart_instead_of_stacking() {
+    let mut app = TestApp::new();
+    let boost_ticks = (PICKUP_FIRE_RATE_BOOST_DURATION * 60.0) as u32;
+
+    app.spawn_pickup(PickupKind::FireRateBoost, Vec2::ZERO);
+    app.advance(boost_ticks / 2);
+    app.spawn_pickup(PickupKind::FireRateBoost, Vec2::ZERO);
+    app.advance(2);
+
+    let player = app.player();
+    let fire_rate = |app: &TestApp| {
+        app.world()
+            .get::<Stats>(player)
+            .unwrap()
+            .apply(Stat::FireRate, 1.0)
+    };
+
+    assert_eq!(fire_rate(&app), PICKUP_FIRE_RATE_BOOST);
+
+    // Past the end of the first boost
+    app.advance(boost_ticks / 2 + 10);
+
+    assert_eq!(fire_rate(&app), PICKUP_FIRE_RATE_BOOST);
+
+    app.advance(boost_ticks / 2);
+
+    assert_eq!(fire_rate(&app), 1.0);
+}
+
+#[test]
+fn pickup_sprites_are_not_map_tiles() {
+    let mut app = TestApp::new();
+
+    for (i, kind) in PickupKind::ALL.into_iter().enumerate() {
+        let pickup = app.spawn_pickup(kind, vec2(300.0 + i as f32 * 50.0, 0.0));
+        let index = app.world().get::<TextureAtlas>(pickup).unwrap().index;
+
+        assert!(!SOLID_TILES.contains(&index), "{kind:?}");
+        assert!(!DECORATION_TILES.contains(&index), "{kind:?}");
+    }
+}
+
+#[test]
+fn bomb_damages_enemies_around_the_player() {
+    let mut app = TestApp::new();
+    let near = app.spawn_enemy(EnemyType::Tank, vec2(300.0, 0.0));
+    let boss = app.spawn_enemy(EnemyType::Boss, vec2(0.0, 400.0));
+    let far = app.spawn_enemy(EnemyType::Grunt, vec2(PICKUP_BOMB_RADIUS + 300.0, 0.0));
+
+    app.spawn_pickup(PickupKind::Bomb, Vec2::ZERO);
+    app.advance(3);
+
+    assert!(!app.exists(near));
+    assert_eq!(
+        app.enemy_health(boss),
+        Some(EnemyType::Boss.archetype().health - PICKUP_BOMB_DAMAGE)
+    );
+    assert_eq!(
+        app.enemy_health(far),
+        Some(EnemyType::Grunt.archetype().health)
+    );
+}
+
+#[test]
+fn magnet_pulls_in_every_gem() {
+    let mut app = TestApp::new();
+    let gem = app.spawn_xp_gem(1, vec2(XP_MAGNET_RADIUS * 3.0, 0.0));
+
+    app.spawn_pickup(PickupKind::Magnet, Vec2::ZERO);
+    app.advance(120);
+
+    assert!(!app.exists(gem));
+}
+
+#[test]
+fn dead_enemies_roll_their_drop_table() {
+    let mut app = TestApp::new();
+
+    app.world_mut().insert_resource(DropTables {
+        enemies: HashMap::from_iter([(
+            EnemyType::Runner,
+            DropTable::new(1.0, &[(PickupKind::Magnet, 1)]),
+        )]),
+        map: DropTable::default(),
+    });
+
+    let runner = app.spawn_enemy(EnemyType::Runner, vec2(500.0, 0.0));
+    let grunt = app.spawn_enemy(EnemyType::Grunt, vec2(-500.0, 0.0));
+
+    for enemy in [runner, grunt] {
+        app.world_mut().get_mut::<Enemy>(enemy).unwrap().health = 0.0;
+    }
+    app.tick();
+
+    let pickups = pickups(&mut app);
+
+    assert_eq!(pickups.len(), 1);
+    assert_eq!(pickups[0].1, PickupKind::Magnet);
+    assert!(app.position(pickups[0].0).distance(vec2(500.0, 0.0)) < 1.0);
+    assert_eq!(
+        app.world_mut().query::<&XpGem>().iter(app.world()).count(),
+        2
+    );
+}
+
+#[test]
+fn drop_tables_respect_their_chance() {
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let never = DropTable::new(0.0, &[(PickupKind::Heal, 1)]);
+    let empty = DropTable::new(1.0, &[]);
+    let always = DropTable::new(1.0, &[(PickupKind::Bomb, 1), (PickupKind::Heal, 0)]);
+
+    for _ in 0..100 {
+        assert_eq!(never.roll(&mut rng), None);
+        assert_eq!(empty.roll(&mut rng), None);
+        assert_eq!(always.roll(&mut rng), Some(PickupKind::Bomb));
+    }
+}
+
+#[test]
+fn maps_place_pickups_on_their_locations() {
+    let map = MapDefinition::parse(
+        br#"(
+            name: "Pickups",
+            legend: {},
+            ground: ["......", "......", "......"],
+            player_spawn: (0, 0),
+            pickups: [(5, 2), (3, 1)],
+        )"#,
+    )
+    .unwrap();
+    let mut app = TestApp::with_map(map);
+
+    let locations = app.world().resource::<TileMap>().pickup_locations.clone();
+    let positions = pickups(&mut app)
+        .into_iter()
+        .map(|(entity, _)| app.position(entity))
+        .collect::<Vec<_>>();
+
+    assert_eq!(positions.len(), 2);
+    assert!(locations
+        .iter()
+        .all(|location| positions.contains(location)));
+}
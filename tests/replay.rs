@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+use shooter2d::{
+    input::PlayerInput,
+    map::MapDefinition,
+    replay::{Replay, ReplayError},
+    state::GameState,
+    testing::TestApp,
+};
+
+const RUN_TICKS: u32 = 3000;
+
+fn courtyard() -> MapDefinition {
+    MapDefinition::load("assets/maps/courtyard.map.ron").unwrap()
+}
+
+/// Circles around while spraying in bursts and taking the first upgrade offered.
+fn scripted_input(tick: u32) -> PlayerInput {
+    let angle = tick as f32 / 40.0;
+
+    PlayerInput {
+        movement: Vec2::from_angle(angle).round(),
+        aim: Some(Vec2::from_angle(-angle) * 200.0),
+        fire: tick % 90 < 30,
+        reload: tick % 600 == 599,
+        upgrade: Some(0),
+        weapon: (tick % 500 == 0).then_some(0),
+    }
+}
+
+/// What the run looked like after one tick.
+#[derive(Debug, PartialEq)]
+struct Snapshot {
+    health: f32,
+    position: Vec2,
+    enemies: usize,
+}
+
+fn snapshot(app: &mut TestApp) -> Snapshot {
+    Snapshot {
+        health: app.player_health(),
+        position: app.player_position(),
+        enemies: app.enemies().len(),
+    }
+}
+
+fn death_tick(snapshots: &[Snapshot]) -> Option<usize> {
+    snapshots.iter().position(|snapshot| snapshot.health <= 0.0)
+}
+
+#[test]
+fn replays_survive_encoding() {
+    let replay = Replay {
+        seed: 0x5EED_1234_ABCD,
+        map: Some(courtyard()),
+        inputs: (0..RUN_TICKS).map(scripted_input).collect(),
+    };
+
+    let bytes = replay.encode();
+    let decoded = Replay::decode(&bytes).unwrap();
+
+    assert_eq!(decoded, replay);
+    assert_eq!(decoded.encode(), bytes);
+}
+
+#[test]
+fn oversized_runs_are_rejected() {
+    let replay = Replay {
+        seed: 1,
+        map: None,
+        inputs: vec![PlayerInput::default()],
+    };
+    let mut bytes = replay.encode();
+
+    // Header, seed and empty map come before the first run length
+    bytes[17..21].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    assert!(matches!(Replay::decode(&bytes), Err(ReplayError::TooLong)));
+}
+
+#[test]
+fn playback_reproduces_the_recorded_run() {
+    let path = std::env::temp_dir().join("shooter2d-playback.replay");
+    let mut app = TestApp::recording(courtyard(), path.clone());
+    let mut recorded = Vec::new();
+
+    for tick in 0..RUN_TICKS {
+        app.set_input(scripted_input(tick));
+        app.tick();
+        recorded.push(snapshot(&mut app));
+
+        if app.player_health() <= 0.0 {
+            break;
+        }
+    }
+
+    // Leaving the run saves the replay
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::GameOver);
+    app.tick();
+
+    let replay = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(recorded.iter().any(|snapshot| snapshot.enemies > 0));
+    assert!(death_tick(&recorded).is_some(), "the player survived");
+
+    let mut playback = TestApp::playing_back(replay);
+    let mut played = Vec::new();
+
+    for _ in 0..recorded.len() {
+        playback.tick();
+        played.push(snapshot(&mut playback));
+    }
+
+    playback.tick();
+
+    assert_eq!(playback.state(), GameState::GameOver);
+    assert_eq!(death_tick(&played), death_tick(&recorded));
+    assert_eq!(played, recorded);
+}
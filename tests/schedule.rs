@@ -0,0 +1,48 @@
+use bevy::{
+    ecs::schedule::{InternedScheduleLabel, LogLevel, ScheduleBuildSettings, ScheduleLabel},
+    prelude::*,
+};
+use shooter2d::headless::{headless_app, HeadlessPlugin};
+
+fn fixed_schedules() -> [InternedScheduleLabel; 3] {
+    [
+        FixedPreUpdate.intern(),
+        FixedUpdate.intern(),
+        FixedPostUpdate.intern(),
+    ]
+}
+
+// Systems racing over the same data run in whatever order the executor picks, which breaks
+// replays and same-seed runs
+#[test]
+fn fixed_schedules_have_no_ambiguities() {
+    let mut app = headless_app();
+    app.add_plugins(HeadlessPlugin {
+        ticks: 1,
+        autopilot: true,
+    });
+
+    for label in fixed_schedules() {
+        app.edit_schedule(label, |schedule| {
+            schedule.set_build_settings(ScheduleBuildSettings {
+                ambiguity_detection: LogLevel::Error,
+                ..default()
+            });
+        });
+    }
+
+    app.finish();
+    app.cleanup();
+
+    let mut errors = Vec::new();
+
+    for label in fixed_schedules() {
+        app.world_mut().schedule_scope(label, |world, schedule| {
+            if let Err(error) = schedule.initialize(world) {
+                errors.push(format!("{label:?}: {error}"));
+            }
+        });
+    }
+
+    assert!(errors.is_empty(), "{}", errors.join("\n"));
+}
@@ -0,0 +1,163 @@
+use bevy::{math::vec2, prelude::*};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use shooter2d::{
+    constants::*,
+    enemy::{get_random_spawn_position, Enemy, EnemyType},
+    headless::HeadlessPlugin,
+    input::PlayerInput,
+    testing::TestApp,
+    wave::WaveStarted,
+    weapon::{ActiveWeapon, Projectile, WeaponDefinition},
+    world::WorldBounds,
+};
+
+const FIRST_SPAWN_TICKS: u32 =
+    ((WAVE_FIRST_DELAY + ENEMY_SPAWN_INTERVAL) * SIMULATION_HZ as f32) as u32 + 1;
+const LONG_RUN_TICKS: u32 = 3600;
+const LONG_RUNS: usize = 4;
+
+fn enemy_snapshot(app: &mut TestApp) -> Vec<(EnemyType, Vec2)> {
+    app.world_mut()
+        .query::<(&Enemy, &Transform)>()
+        .iter(app.world())
+        .map(|(enemy, transform)| (enemy.kind, transform.translation.truncate()))
+        .collect()
+}
+
+#[test]
+fn spawned_enemy_uses_archetype_stats() {
+    let mut app = TestApp::new();
+
+    for kind in EnemyType::ALL {
+        let enemy = app.spawn_enemy(kind, vec2(2000.0, 0.0));
+
+        assert_eq!(app.enemy_health(enemy), Some(kind.archetype().health));
+    }
+}
+
+#[test]
+fn first_wave_spawns_enemies_around_player() {
+    let mut app = TestApp::with_waves();
+    app.record_events::<WaveStarted>();
+
+    app.advance(FIRST_SPAWN_TICKS);
+
+    let started = app.events::<WaveStarted>();
+    assert_eq!(started.len(), 1);
+    assert_eq!(started[0].wave, 1);
+
+    let enemies = app.enemies();
+    let player_position = app.player_position();
+
+    assert!(!enemies.is_empty());
+    assert!(enemies.len() <= ENEMY_SPAWN_BATCH_SIZE);
+
+    for enemy in enemies {
+        let distance = app.position(enemy).distance(player_position);
+
+        assert!((900.0..5100.0).contains(&distance), "{distance}");
+    }
+}
+
+#[test]
+fn no_enemies_spawn_without_waves() {
+    let mut app = TestApp::new();
+
+    app.advance(FIRST_SPAWN_TICKS);
+
+    assert!(app.enemies().is_empty());
+}
+
+/// Enemies after every tick of a run with waves, the autopilot fighting them off.
+fn long_run() -> Vec<Vec<(EnemyType, Vec2)>> {
+    let mut app = TestApp::with_waves();
+    app.app.add_plugins(HeadlessPlugin {
+        ticks: u32::MAX,
+        autopilot: true,
+    });
+    app.record_events::<WaveStarted>();
+
+    let snapshots = (0..LONG_RUN_TICKS)
+        .map(|_| {
+            app.tick();
+            enemy_snapshot(&mut app)
+        })
+        .collect();
+
+    assert!(app.events::<WaveStarted>().len() >= 2, "no second wave");
+
+    snapshots
+}
+
+#[test]
+fn same_seed_spawns_same_enemies() {
+    let expected = long_run();
+
+    for _ in 1..LONG_RUNS {
+        let snapshots = long_run();
+
+        for (tick, (snapshot, expected)) in snapshots.iter().zip(&expected).enumerate() {
+            assert_eq!(snapshot, expected, "runs diverged on tick {tick}");
+        }
+    }
+}
+
+#[test]
+fn firing_spawns_one_projectile_per_pellet() {
+    let mut app = TestApp::new();
+
+    app.set_input(PlayerInput {
+        aim: Some(vec2(100.0, 0.0)),
+        fire: true,
+        ..default()
+    });
+
+    // The first shot is ready once a full fire interval has passed
+    let definition = {
+        let world = app.world_mut();
+        let handle = world
+            .query_filtered::<&Handle<WeaponDefinition>, With<ActiveWeapon>>()
+            .single(world)
+            .clone();
+
+        world
+            .resource::<Assets<WeaponDefinition>>()
+            .get(&handle)
+            .unwrap()
+            .clone()
+    };
+    let interval = (SIMULATION_HZ as f32 / definition.fire_rate).ceil() as u32;
+
+    app.advance(interval);
+
+    let projectiles = app
+        .world_mut()
+        .query::<&Projectile>()
+        .iter(app.world())
+        .count();
+
+    assert_eq!(projectiles, definition.pellets_per_shot as usize);
+}
+
+#[test]
+fn spawns_stay_away_from_a_player_in_a_corner() {
+    let bounds = WorldBounds(Rect::new(-WORLD_W, -WORLD_H, WORLD_W, WORLD_H));
+    let mut rng = ChaCha8Rng::seed_from_u64(11);
+
+    let corners = [
+        bounds.0.min,
+        bounds.0.max,
+        vec2(WORLD_W - 10.0, 10.0 - WORLD_H),
+    ];
+
+    for player in corners {
+        for _ in 0..500 {
+            let (x, y) = get_random_spawn_position(&mut rng, &bounds, &[], player);
+            let position = vec2(x, y);
+
+            assert!(bounds.0.contains(position));
+            assert!(position.distance(player) >= ENEMY_SPAWN_MIN_DISTANCE - 0.01);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::time::Duration;
+
+use bevy::{math::vec2, prelude::*};
+use shooter2d::{
+    constants::*,
+    enemy::{Enemy, EnemyType},
+    input::PlayerInput,
+    stats::{Modifier, ModifierSource, Stat, Stats},
+    testing::TestApp,
+};
+
+fn player_stats(app: &mut TestApp) -> Mut<'_, Stats> {
+    let player = app.player();
+
+    app.world_mut().get_mut::<Stats>(player).unwrap()
+}
+
+#[test]
+fn modifiers_add_before_multiplying() {
+    let mut stats = Stats::default().with_base(Stat::Damage, 10.0);
+
+    stats.add_modifier(Modifier::multiply(
+        Stat::Damage,
+        2.0,
+        ModifierSource::Upgrade,
+    ));
+    stats.add_modifier(Modifier::add(Stat::Damage, 5.0, ModifierSource::Pickup));
+    stats.add_modifier(Modifier::multiply(
+        Stat::Damage,
+        1.5,
+        ModifierSource::Pickup,
+    ));
+    stats.add_modifier(Modifier::add(Stat::Speed, 100.0, ModifierSource::Pickup));
+
+    assert_eq!(stats.get(Stat::Damage), (10.0 + 5.0) * 2.0 * 1.5);
+    assert_eq!(stats.apply(Stat::Damage, 2.0), (2.0 + 5.0) * 2.0 * 1.5);
+    assert_eq!(stats.get(Stat::Speed), 100.0);
+
+    stats.remove_source(ModifierSource::Pickup);
+
+    assert_eq!(stats.get(Stat::Damage), 20.0);
+    assert_eq!(stats.modifiers().len(), 1);
+}
+
+#[test]
+fn timed_modifiers_expire() {
+    let mut stats = Stats::default().with_base(Stat::FireRate, 1.0);
+
+    stats
+        .add_modifier(Modifier::multiply(Stat::FireRate, 2.0, ModifierSource::Pickup).lasting(1.0));
+    stats.add_modifier(Modifier::add(Stat::FireRate, 1.0, ModifierSource::Upgrade));
+
+    stats.tick(Duration::from_secs_f32(0.5));
+    assert_eq!(stats.get(Stat::FireRate), 4.0);
+
+    stats.tick(Duration::from_secs_f32(0.6));
+    assert_eq!(stats.get(Stat::FireRate), 2.0);
+}
+
+#[test]
+fn refreshed_modifiers_restart_instead_of_stacking() {
+    let mut stats = Stats::default().with_base(Stat::FireRate, 1.0);
+    let boost = Modifier::multiply(Stat::FireRate, 2.0, ModifierSource::Pickup).lasting(1.0);
+
+    stats.refresh_modifier(boost.clone());
+    stats.tick(Duration::from_secs_f32(0.8));
+    stats.refresh_modifier(boost);
+
+    assert_eq!(stats.get(Stat::FireRate), 2.0);
+    assert_eq!(stats.modifiers().len(), 1);
+
+    stats.tick(Duration::from_secs_f32(0.8));
+    assert_eq!(stats.get(Stat::FireRate), 2.0);
+
+    stats.tick(Duration::from_secs_f32(0.3));
+    assert_eq!(stats.get(Stat::FireRate), 1.0);
+}
+
+#[test]
+fn speed_modifiers_change_player_movement() {
+    let mut app = TestApp::new();
+
+    player_stats(&mut app).add_modifier(Modifier::multiply(
+        Stat::Speed,
+        2.0,
+        ModifierSource::Pickup,
+    ));
+    app.set_input(PlayerInput {
+        movement: Vec2::X,
+        ..default()
+    });
+
+    let start = app.player_position();
+    app.advance(60);
+
+    // One second of movement at twice the base speed
+    let travelled = app.player_position().x - start.x;
+    assert!((travelled - PLAYER_SPEED * 2.0).abs() < 1.0);
+}
+
+#[test]
+fn expired_max_health_boost_caps_health() {
+    let mut app = TestApp::new();
+
+    player_stats(&mut app)
+        .add_modifier(Modifier::add(Stat::MaxHealth, 50.0, ModifierSource::Pickup).lasting(0.5));
+    app.set_player_health(PLAYER_HEALTH + 50.0);
+    app.advance(16);
+
+    assert_eq!(app.player_health(), PLAYER_HEALTH + 50.0);
+
+    app.advance(32);
+
+    assert_eq!(app.player_health(), PLAYER_HEALTH);
+}
+
+#[test]
+fn enemy_contact_damage_reads_its_stats() {
+    let mut app = TestApp::new();
+    let enemy = app.spawn_enemy(EnemyType::Tank, vec2(10.0, 0.0));
+
+    app.world_mut()
+        .get_mut::<Stats>(enemy)
+        .unwrap()
+        .add_modifier(Modifier::multiply(
+            Stat::Damage,
+            0.5,
+            ModifierSource::Pickup,
+        ));
+    app.advance(3);
+
+    assert_eq!(
+        app.player_health(),
+        PLAYER_HEALTH - EnemyType::Tank.archetype().damage * 0.5
+    );
+}
+
+#[test]
+fn enemies_spawn_with_their_max_health_stat() {
+    let mut stats = EnemyType::Tank.archetype().stats();
+    stats.add_modifier(Modifier::multiply(
+        Stat::MaxHealth,
+        2.0,
+        ModifierSource::Upgrade,
+    ));
+
+    let enemy = Enemy::new(EnemyType::Tank, &stats);
+
+    assert_eq!(enemy.health, EnemyType::Tank.archetype().health * 2.0);
+}
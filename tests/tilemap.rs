@@ -0,0 +1,135 @@
+use bevy::{math::vec2, prelude::*};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use shooter2d::{
+    collision::Collider,
+    constants::*,
+    enemy::EnemyType,
+    input::PlayerInput,
+    testing::{weapon_definition, TestApp},
+    tilemap::{TileMap, TileMapGenerator},
+    world::WorldBounds,
+};
+
+#[test]
+fn player_stops_at_a_solid_tile() {
+    let mut app = TestApp::new();
+    let tile = vec2(4.0 * WORLD_TILE_SIZE, 0.0);
+
+    app.spawn_solid_tile(tile);
+    app.set_input(PlayerInput {
+        movement: vec2(1.0, 0.0),
+        ..default()
+    });
+    app.advance(120);
+
+    let player = app.player();
+    let half_extents = app.world().get::<Collider>(player).unwrap().half_extents();
+    let position = app.player_position();
+
+    assert!((position.x - (tile.x - WORLD_TILE_SIZE / 2.0 - half_extents.x)).abs() < 0.01);
+    assert!(position.y.abs() < 0.01);
+}
+
+#[test]
+fn player_slides_along_a_solid_tile() {
+    let mut app = TestApp::new();
+    let tile = vec2(4.0 * WORLD_TILE_SIZE, 0.0);
+
+    app.spawn_solid_tile(tile);
+
+    let player = app.player();
+    let half_extents = app.world().get::<Collider>(player).unwrap().half_extents();
+    let face = tile.x - WORLD_TILE_SIZE / 2.0 - half_extents.x;
+
+    app.set_player_position(vec2(face, 0.0));
+    app.set_input(PlayerInput {
+        movement: vec2(1.0, 1.0),
+        ..default()
+    });
+    app.advance(10);
+
+    // Blocked horizontally while still moving up along the tile
+    let position = app.player_position();
+
+    assert!((position.x - face).abs() < 0.01);
+    assert!(position.y > 0.0);
+}
+
+#[test]
+fn enemies_are_pushed_out_of_solid_tiles() {
+    let mut app = TestApp::new();
+    let tile = vec2(8.0 * WORLD_TILE_SIZE, 0.0);
+
+    app.spawn_solid_tile(tile);
+
+    let enemy = app.spawn_enemy(EnemyType::Grunt, tile + vec2(5.0, 0.0));
+    app.advance(1);
+
+    let collider = *app.world().get::<Collider>(enemy).unwrap();
+    let tile_collider = Collider::Aabb {
+        half_extents: Vec2::splat(WORLD_TILE_SIZE / 2.0),
+    };
+
+    assert!(collider
+        .penetration(app.position(enemy), &tile_collider, tile)
+        .is_none());
+}
+
+#[test]
+fn projectiles_despawn_on_solid_tiles() {
+    let mut app = TestApp::new();
+
+    app.spawn_solid_tile(vec2(200.0, 0.0));
+
+    let projectile = app.spawn_projectile(&weapon_definition(), vec2(50.0, 0.0), Vec2::X);
+    app.advance(30);
+
+    assert!(!app.exists(projectile));
+}
+
+#[test]
+fn generated_map_keeps_the_spawn_clear() {
+    let generator = TileMapGenerator::default();
+
+    for seed in 0..8 {
+        let map = TileMap::generate(&generator, &mut ChaCha8Rng::seed_from_u64(seed));
+
+        assert!(map.tiles().any(|tile| map.is_solid(tile)));
+
+        for tile in map.tiles().filter(|tile| map.is_solid(*tile)) {
+            assert!(map.tile_center(tile).length() > generator.spawn_clearance);
+        }
+    }
+}
+
+#[test]
+fn generated_map_has_ground_on_open_tiles() {
+    let map = TileMap::generate(
+        &TileMapGenerator::default(),
+        &mut ChaCha8Rng::seed_from_u64(0),
+    );
+    let ground = map
+        .tiles()
+        .filter(|tile| map.ground[map.index(*tile)].is_some())
+        .collect::<Vec<_>>();
+
+    assert!(!ground.is_empty());
+
+    for tile in ground {
+        assert!(!map.is_solid(tile));
+        assert!(GROUND_TILES.contains(&map.ground[map.index(tile)].unwrap()));
+    }
+}
+
+#[test]
+fn world_bounds_match_the_map() {
+    let app = TestApp::new();
+    let map = app.world().resource::<TileMap>();
+
+    assert_eq!(app.world().resource::<WorldBounds>().0, map.bounds());
+    assert_eq!(
+        map.tile_at(Vec2::ZERO),
+        Some(UVec2::new(map.width / 2, map.height / 2))
+    );
+}
@@ -0,0 +1,261 @@
+use bevy::{math::vec2, prelude::*};
+use shooter2d::{
+    constants::*,
+    enemy::EnemyType,
+    input::PlayerInput,
+    replay::Replay,
+    stats::{Modifier, ModifierSource, Stat, Stats},
+    testing::TestApp,
+    weapon::{
+        ActiveWeapon, Inventory, Orbiting, Projectile, WeaponDefinition, WeaponDefinitionError,
+    },
+};
+
+fn inventory(app: &mut TestApp) -> (Vec<Entity>, usize, Vec<Entity>) {
+    let player = app.player();
+    let inventory = app.world().get::<Inventory>(player).unwrap();
+
+    (
+        inventory.weapons.clone(),
+        inventory.active,
+        inventory.orbiting.clone(),
+    )
+}
+
+fn definition(app: &mut TestApp, weapon: Entity) -> WeaponDefinition {
+    let handle = app
+        .world()
+        .get::<Handle<WeaponDefinition>>(weapon)
+        .unwrap()
+        .clone();
+
+    app.world()
+        .resource::<Assets<WeaponDefinition>>()
+        .get(&handle)
+        .unwrap()
+        .clone()
+}
+
+fn projectile_damage(app: &mut TestApp) -> Vec<f32> {
+    app.world_mut()
+        .query::<&Projectile>()
+        .iter(app.world())
+        .map(|projectile| projectile.damage)
+        .collect()
+}
+
+#[test]
+fn player_starts_with_every_starting_weapon() {
+    let mut app = TestApp::new();
+    let (weapons, active, orbiting) = inventory(&mut app);
+
+    assert_eq!(weapons.len(), PLAYER_STARTING_WEAPONS.len());
+    assert_eq!(active, 0);
+    assert!(orbiting.is_empty());
+
+    for (slot, weapon) in weapons.iter().enumerate() {
+        let visibility = *app.world().get::<Visibility>(*weapon).unwrap();
+
+        assert_eq!(
+            app.world().get::<ActiveWeapon>(*weapon).is_some(),
+            slot == 0
+        );
+        assert_eq!(visibility == Visibility::Hidden, slot != 0);
+    }
+}
+
+#[test]
+fn switching_fires_the_selected_weapon() {
+    let mut app = TestApp::new();
+    let (weapons, ..) = inventory(&mut app);
+    let rifle = definition(&mut app, weapons[2]);
+
+    app.set_input(PlayerInput {
+        weapon: Some(2),
+        ..default()
+    });
+    app.tick();
+
+    assert_eq!(inventory(&mut app).1, 2);
+    assert!(app.world().get::<ActiveWeapon>(weapons[2]).is_some());
+    assert!(app.world().get::<ActiveWeapon>(weapons[0]).is_none());
+
+    app.set_input(PlayerInput {
+        aim: Some(vec2(100.0, 0.0)),
+        fire: true,
+        ..default()
+    });
+    app.advance((SIMULATION_HZ as f32 / rifle.fire_rate).ceil() as u32);
+
+    let damage = projectile_damage(&mut app);
+
+    assert_eq!(damage.len(), rifle.pellets_per_shot as usize);
+    assert!(damage
+        .iter()
+        .all(|damage| *damage == rifle.projectile_damage));
+}
+
+#[test]
+fn switching_to_an_empty_slot_is_ignored() {
+    let mut app = TestApp::new();
+
+    app.set_input(PlayerInput {
+        weapon: Some(8),
+        ..default()
+    });
+    app.tick();
+
+    assert_eq!(inventory(&mut app).1, 0);
+}
+
+#[test]
+fn orbiting_weapons_follow_the_stat() {
+    let mut app = TestApp::new();
+    let player = app.player();
+
+    for _ in 0..2 {
+        app.world_mut()
+            .get_mut::<Stats>(player)
+            .unwrap()
+            .add_modifier(Modifier::add(
+                Stat::OrbitingWeapons,
+                1.0,
+                ModifierSource::Upgrade,
+            ));
+    }
+    app.advance(2);
+
+    let (_, _, orbiting) = inventory(&mut app);
+    let player_position = app.player_position();
+
+    assert_eq!(orbiting.len(), 2);
+
+    for weapon in &orbiting {
+        let distance = app.position(*weapon).distance(player_position);
+
+        assert!((distance - WEAPON_ORBIT_RADIUS).abs() < 1.0);
+    }
+
+    // Spaced evenly around the player
+    let offsets = orbiting
+        .iter()
+        .map(|weapon| app.position(*weapon) - player_position)
+        .collect::<Vec<_>>();
+    assert!((offsets[0] + offsets[1]).length() < 1.0);
+
+    app.world_mut()
+        .get_mut::<Stats>(player)
+        .unwrap()
+        .remove_source(ModifierSource::Upgrade);
+    app.tick();
+
+    assert!(inventory(&mut app).2.is_empty());
+    assert!(orbiting.iter().all(|weapon| !app.exists(*weapon)));
+}
+
+#[test]
+fn orbiting_weapons_fire_on_their_own() {
+    let mut app = TestApp::new();
+    let player = app.player();
+
+    app.world_mut()
+        .get_mut::<Stats>(player)
+        .unwrap()
+        .add_modifier(Modifier::add(
+            Stat::OrbitingWeapons,
+            1.0,
+            ModifierSource::Upgrade,
+        ));
+    app.tick();
+
+    let (_, _, orbiting) = inventory(&mut app);
+    let turret = definition(&mut app, orbiting[0]);
+
+    // Nothing to shoot at
+    app.advance(SIMULATION_HZ as u32);
+    assert!(projectile_damage(&mut app).is_empty());
+
+    let enemy = app.spawn_enemy(EnemyType::Tank, vec2(turret.range() / 2.0, 0.0));
+    app.advance(SIMULATION_HZ as u32);
+
+    let target = app.world().get::<Orbiting>(orbiting[0]).unwrap().target;
+    let health = app.enemy_health(enemy).unwrap();
+    let damage = EnemyType::Tank.archetype().health - health;
+
+    assert!(target.is_some());
+    assert!(damage > 0.0);
+    assert_eq!(damage % turret.projectile_damage, 0.0);
+}
+
+#[test]
+fn replays_keep_weapon_switches() {
+    let replay = Replay {
+        seed: 5,
+        map: None,
+        inputs: vec![
+            PlayerInput {
+                weapon: Some(1),
+                fire: true,
+                ..default()
+            },
+            PlayerInput {
+                weapon: Some(0),
+                upgrade: Some(2),
+                ..default()
+            },
+            PlayerInput::default(),
+        ],
+    };
+
+    assert_eq!(Replay::decode(&replay.encode()).unwrap(), replay);
+}
+
+fn smg() -> String {
+    std::fs::read_to_string("assets/weapons/smg.weapon.ron").unwrap()
+}
+
+#[test]
+fn shipped_weapons_are_valid() {
+    for path in PLAYER_STARTING_WEAPONS.into_iter().chain([ORBITING_WEAPON]) {
+        let bytes = std::fs::read(format!("assets/{path}")).unwrap();
+
+        if let Err(err) = WeaponDefinition::parse(&bytes) {
+            panic!("{path}: {err}");
+        }
+    }
+}
+
+#[test]
+fn invalid_weapon_stats_are_rejected() {
+    let cases = [
+        ("spread: 0.2", "spread: -0.2", "spread"),
+        ("fire_rate: 20.0", "fire_rate: -1.0", "fire_rate"),
+        ("fire_rate: 20.0", "fire_rate: 0.0", "fire_rate"),
+        (
+            "pellets_per_shot: 1",
+            "pellets_per_shot: 0",
+            "pellets_per_shot",
+        ),
+        (
+            "magazine_size: Some(30)",
+            "magazine_size: Some(0)",
+            "magazine_size",
+        ),
+        ("reload_time: 1.5", "reload_time: -1.5", "reload_time"),
+        (
+            "projectile_lifetime: 0.8",
+            "projectile_lifetime: 0.0",
+            "projectile_lifetime",
+        ),
+    ];
+
+    for (valid, invalid, field) in cases {
+        let definition = smg().replace(valid, invalid);
+
+        match WeaponDefinition::parse(definition.as_bytes()) {
+            Err(WeaponDefinitionError::NotPositive(rejected))
+            | Err(WeaponDefinitionError::Negative(rejected)) => assert_eq!(rejected, field),
+            other => panic!("{invalid} was not rejected: {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use bevy::{math::vec2, prelude::*};
+use shooter2d::{
+    collision::Collider,
+    constants::*,
+    enemy::EnemyType,
+    input::PlayerInput,
+    testing::{weapon_definition, TestApp},
+    world::WorldBounds,
+};
+
+fn bounds(app: &TestApp) -> Rect {
+    app.world().resource::<WorldBounds>().0
+}
+
+#[test]
+fn player_cannot_walk_past_the_edge() {
+    let mut app = TestApp::new();
+    let edge = bounds(&app).max;
+
+    app.set_player_position(edge - vec2(5.0, 5.0));
+    app.set_input(PlayerInput {
+        movement: vec2(1.0, 1.0),
+        ..default()
+    });
+    app.advance(60);
+
+    let player = app.player();
+    let half_extents = app.world().get::<Collider>(player).unwrap().half_extents();
+
+    assert_eq!(app.player_position(), edge - half_extents);
+}
+
+#[test]
+fn enemies_stay_inside_the_world() {
+    let mut app = TestApp::new();
+    let area = bounds(&app);
+
+    app.set_player_position(vec2(area.min.x, 0.0));
+
+    let enemy = app.spawn_enemy(EnemyType::Runner, vec2(area.min.x - 500.0, 0.0));
+    app.advance(1);
+
+    assert!(area.contains(app.position(enemy)));
+}
+
+#[test]
+fn projectiles_despawn_at_the_edge() {
+    let mut app = TestApp::new();
+    let area = bounds(&app);
+
+    let definition = weapon_definition();
+    let projectile = app.spawn_projectile(&definition, vec2(area.max.x - 20.0, 0.0), Vec2::X);
+
+    app.advance(5);
+
+    assert!(!app.exists(projectile));
+}
+
+#[test]
+fn wave_enemies_spawn_inside_the_world() {
+    let mut app = TestApp::with_waves();
+    let area = bounds(&app);
+
+    // From a corner most spawn candidates fall outside the arena
+    app.set_player_position(area.max - Vec2::splat(100.0));
+    app.advance(((WAVE_FIRST_DELAY + ENEMY_SPAWN_INTERVAL) * SIMULATION_HZ as f32) as u32 + 1);
+
+    let enemies = app.enemies();
+
+    assert!(!enemies.is_empty());
+
+    for enemy in enemies {
+        assert!(area.contains(app.position(enemy)));
+    }
+}